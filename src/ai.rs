@@ -0,0 +1,317 @@
+//! The computer opponent. Rather than chasing the ball, it works out where
+//! the ball will cross its paddle, bounces included, and lines up so that
+//! its return heads as far away from the other paddle as it can. How well
+//! it manages this depends on the difficulty it is set to.
+use std::fmt;
+use std::str::FromStr;
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::ball::{ball_bounds, BALL_SIZE};
+use crate::controller::{BallState, GameSnapshot, PaddleController};
+use crate::paddle::PADDLE_WIDTH;
+use crate::rng::GameRng;
+use crate::rules::MatchRules;
+
+// How many points along the paddle are tried when picking where to strike the ball
+const AIM_CANDIDATES: usize = 9;
+// Fraction of the paddle that is used for aiming, keeping away from its very edges
+const AIM_REACH: f32 = 0.8;
+
+/// How well the computer plays. The presets of each `Difficulty` are made of
+/// these, so that the computer misjudges the ball like a person would.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComputerSkill {
+    /// Seconds it takes to react after the ball changes direction
+    pub reaction_delay: f32,
+    /// Largest distance in pixels that a prediction can be off by
+    pub prediction_noise: f32,
+    /// Fraction of `paddle_speed` that the computer may move at
+    pub max_speed: f32,
+    /// Chance, from 0 to 1, of letting the ball past on purpose
+    pub miss_chance: f64,
+}
+
+/// The difficulty presets that single player matches are played at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Impossible,
+}
+
+impl Difficulty {
+    pub fn skill(self) -> ComputerSkill {
+        match self {
+            Difficulty::Easy => ComputerSkill {
+                reaction_delay: 0.35,
+                prediction_noise: 60.0,
+                max_speed: 0.55,
+                miss_chance: 0.15,
+            },
+            Difficulty::Normal => ComputerSkill {
+                reaction_delay: 0.2,
+                prediction_noise: 30.0,
+                max_speed: 0.8,
+                miss_chance: 0.05,
+            },
+            Difficulty::Hard => ComputerSkill {
+                reaction_delay: 0.1,
+                prediction_noise: 10.0,
+                max_speed: 0.9,
+                miss_chance: 0.02,
+            },
+            Difficulty::Impossible => ComputerSkill {
+                reaction_delay: 0.0,
+                prediction_noise: 0.0,
+                max_speed: 1.0,
+                miss_chance: 0.0,
+            },
+        }
+    }
+
+    /// The next difficulty up, wrapping back around to the easiest
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Impossible,
+            Difficulty::Impossible => Difficulty::Easy,
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            "impossible" => Ok(Difficulty::Impossible),
+            _ => Err(format!("`{name}` is not a difficulty")),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Impossible => "Impossible",
+        })
+    }
+}
+
+/// What the computer remembers between steps about the ball it is chasing.
+/// It has its own source of randomness, as the computer only thinks during
+/// matches and must not throw off the rest of the match when replayed.
+#[derive(Component)]
+pub struct ComputerMind {
+    rng: GameRng,
+    // Which way the ball was last seen heading
+    heading: f32,
+    // Seconds left until the computer reacts to the ball changing direction
+    reaction_timer: f32,
+    // How far off the current prediction is, and whether to miss on purpose
+    error: f32,
+    miss: bool,
+    target: f32,
+}
+
+impl ComputerMind {
+    pub fn new(seed: u64) -> Self {
+        ComputerMind {
+            rng: GameRng::from_seed(seed),
+            heading: 0.0,
+            reaction_timer: 0.0,
+            error: 0.0,
+            miss: false,
+            target: 0.0,
+        }
+    }
+
+    /// Decides how the computer moves a paddle on this step, as a
+    /// `PaddleInput` value from -1 to 1. Must only be asked on steps that are
+    /// played, as its reactions and rolls count on every call.
+    pub fn input(&mut self, skill: &ComputerSkill, snapshot: &GameSnapshot) -> f32 {
+        let GameSnapshot {
+            paddle,
+            opponent,
+            ball,
+            rules,
+            delta_seconds,
+            ..
+        } = *snapshot;
+
+        // Every change in direction takes a moment to notice, and is judged
+        // a little differently each time
+        let heading = ball.velocity.x.signum();
+        if heading != self.heading {
+            self.heading = heading;
+            self.reaction_timer = skill.reaction_delay;
+            self.error = if skill.prediction_noise > 0.0 {
+                self.rng
+                    .gen_range(-skill.prediction_noise..=skill.prediction_noise)
+            } else {
+                0.0
+            };
+            self.miss = self.rng.gen_bool(skill.miss_chance);
+        }
+
+        if self.reaction_timer > 0.0 {
+            self.reaction_timer -= delta_seconds;
+        } else {
+            self.target = match aim(paddle, opponent, ball, rules) {
+                Some((intercept_y, _)) if self.miss => {
+                    // Step aside towards the middle, where the border can't
+                    // stop the paddle from getting out of the way
+                    let aside = rules.paddle_height / 2.0 + BALL_SIZE;
+                    intercept_y + (board_center() - intercept_y).signum() * aside
+                }
+                Some((_, target)) => target + self.error,
+                None => board_center(),
+            };
+        }
+
+        // Slow down on approach so the paddle settles instead of jittering
+        // around the target
+        let step_distance = rules.paddle_speed * skill.max_speed * delta_seconds;
+        ((self.target - paddle.y) / step_distance).clamp(-1.0, 1.0) * skill.max_speed
+    }
+}
+
+/// The computer as a `PaddleController`, so that it can play on either side
+pub struct ComputerController {
+    skill: ComputerSkill,
+    mind: ComputerMind,
+}
+
+impl ComputerController {
+    pub fn new(difficulty: Difficulty, seed: u64) -> Self {
+        ComputerController {
+            skill: difficulty.skill(),
+            mind: ComputerMind::new(seed),
+        }
+    }
+}
+
+impl PaddleController for ComputerController {
+    fn movement(&mut self, snapshot: &GameSnapshot) -> f32 {
+        self.mind.input(&self.skill, snapshot)
+    }
+}
+
+// Where the ball coming towards the paddle will cross it, if it is, along
+// with where the paddle should be to meet it
+fn aim(
+    paddle: Vec2,
+    opponent: Option<Vec2>,
+    ball: BallState,
+    rules: &MatchRules,
+) -> Option<(f32, f32)> {
+    let face_x = face_of(paddle.x);
+    let intercept_y = predict_intercept(ball.position, ball.velocity, face_x)?;
+
+    let Some(opponent) = opponent else {
+        return Some((intercept_y, intercept_y));
+    };
+    let intercept = Vec2::new(face_x, intercept_y);
+    let opponent_face_x = face_of(opponent.x);
+
+    // Try striking the ball with different parts of the paddle, and keep the
+    // one whose return arrives furthest from the other paddle
+    let reach = rules.paddle_height / 2.0 * AIM_REACH;
+    let mut best = (f32::NEG_INFINITY, intercept_y);
+    for candidate in 0..AIM_CANDIDATES {
+        let offset = -reach + 2.0 * reach * candidate as f32 / (AIM_CANDIDATES - 1) as f32;
+        let paddle_center = Vec2::new(paddle.x, intercept_y - offset);
+        let velocity = return_velocity(intercept, paddle_center, rules.ball_speed);
+        let Some(arrival_y) = predict_intercept(intercept, velocity, opponent_face_x) else {
+            continue;
+        };
+        let distance = (arrival_y - opponent.y).abs();
+        if distance > best.0 {
+            best = (distance, paddle_center.y);
+        }
+    }
+    Some((intercept_y, best.1))
+}
+
+/// The ball that the paddle at `paddle_x` has to deal with first. That is the
+/// one that will reach it soonest, or the closest one when none are heading
+/// its way.
+pub fn most_threatening(
+    balls: impl IntoIterator<Item = BallState>,
+    paddle_x: f32,
+) -> Option<BallState> {
+    let face_x = face_of(paddle_x);
+    let rank = |ball: &BallState| {
+        let distance = face_x - ball.position.x;
+        if ball.velocity.x != 0.0 && distance.signum() == ball.velocity.x.signum() {
+            (false, distance / ball.velocity.x)
+        } else {
+            (true, distance.abs())
+        }
+    };
+    balls.into_iter().min_by(|first, second| {
+        let (first_away, first_rank) = rank(first);
+        let (second_away, second_rank) = rank(second);
+        first_away
+            .cmp(&second_away)
+            .then(first_rank.total_cmp(&second_rank))
+    })
+}
+
+/// Predicts the height at which the ball will reach `target_x`, following
+/// it as it bounces off of the top and bottom borders. Returns `None` if the
+/// ball is moving away from `target_x`.
+pub fn predict_intercept(position: Vec2, velocity: Vec2, target_x: f32) -> Option<f32> {
+    let distance = target_x - position.x;
+    if velocity.x == 0.0 || distance.signum() != velocity.x.signum() {
+        return None;
+    }
+    let seconds = distance / velocity.x;
+    let (y_min, y_max) = ball_bounds();
+    Some(fold_between(
+        position.y + velocity.y * seconds,
+        y_min,
+        y_max,
+    ))
+}
+
+// The same model `ball_movement` uses: the ball leaves along the line from
+// the center of the paddle through the ball
+fn return_velocity(ball: Vec2, paddle_center: Vec2, ball_speed: f32) -> Vec2 {
+    (ball - paddle_center).normalize() * ball_speed
+}
+
+// Halfway between the bottom border and the scorebar
+fn board_center() -> f32 {
+    let (y_min, y_max) = ball_bounds();
+    (y_min + y_max) / 2.0
+}
+
+// Where the center of the ball is when it touches the front of a paddle
+fn face_of(paddle_x: f32) -> f32 {
+    paddle_x - paddle_x.signum() * (PADDLE_WIDTH + BALL_SIZE) / 2.0
+}
+
+// Mirrors a height back into the range as many times as the ball would
+// bounce on its way there
+fn fold_between(y: f32, min: f32, max: f32) -> f32 {
+    let span = max - min;
+    let folded = (y - min).rem_euclid(2.0 * span);
+    if folded > span {
+        max - (folded - span)
+    } else {
+        min + folded
+    }
+}
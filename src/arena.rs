@@ -0,0 +1,413 @@
+//! The layout of the board that a match is played on: the walls that the ball
+//! bounces off of, the obstacles standing or moving about in its way, how wide
+//! the goals are and where each paddle is free to move.
+//! Arenas are kept in files under `assets/arenas`, and the one picked on the
+//! menu is stored in replays along with the rest of the match setup.
+use std::error::Error;
+use std::f32::consts::TAU;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::ball::BALL_SIZE;
+use crate::game::MatchClock;
+use crate::paddle::{Side, PADDLE_WIDTH};
+use crate::physics::{closest_point_on_segment, penetration, PhysicsSet, Position};
+use crate::{match_in_progress, UI_HEIGHT, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+/// How thick the walls are drawn, and how far the ball is kept from the line
+/// running down the middle of each of them
+pub const WALL_THICKNESS: f32 = 8.;
+
+pub struct ArenaPlugin;
+
+impl Plugin for ArenaPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            move_obstacles
+                .in_set(PhysicsSet::Paddles)
+                .run_if(match_in_progress),
+        );
+    }
+}
+
+/// Everything that makes up the board besides the ball and paddles. The
+/// default is the classic board, with nothing in the way and the whole of
+/// each goal line open.
+///
+/// Any field left out of an arena file keeps its default value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Arena {
+    /// What the arena is called on the menu
+    pub name: String,
+    /// Height of the opening in the middle of each goal line, with the rest
+    /// of the line walled off. Left out, the whole goal line is open.
+    pub goal_height: Option<f32>,
+    /// Where the left paddle is free to move
+    pub left_lane: Lane,
+    /// Where the right paddle is free to move
+    pub right_lane: Lane,
+    /// Walls for the ball to bounce off of, which may lie at any angle
+    pub walls: Vec<Segment>,
+    /// Boxes in the way of the ball, some of which move about
+    pub obstacles: Vec<Obstacle>,
+}
+
+/// The stretch of the board that a paddle is kept within
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Lane {
+    /// How far in from its own goal line the center of the paddle sits
+    pub inset: f32,
+    /// Lowest that the bottom of the paddle can go
+    pub bottom: f32,
+    /// Highest that the top of the paddle can go
+    pub top: f32,
+}
+
+/// A straight wall between two points
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Segment {
+    pub start: Vec2,
+    pub end: Vec2,
+}
+
+/// An axis aligned box in the way of the ball
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Obstacle {
+    /// Where the middle of the box is, or where it moves about if it moves
+    pub center: Vec2,
+    pub half_size: Vec2,
+    /// Sends the box back and forth. Left out, it stands still.
+    #[serde(default)]
+    pub motion: Option<ObstacleMotion>,
+}
+
+/// Swings an obstacle smoothly out to `offset` from its center, back through
+/// it to the opposite side and home again, once every `period` seconds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ObstacleMotion {
+    pub offset: Vec2,
+    pub period: f32,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Arena {
+            name: "Classic".to_string(),
+            goal_height: None,
+            left_lane: Lane::default(),
+            right_lane: Lane::default(),
+            walls: Vec::new(),
+            obstacles: Vec::new(),
+        }
+    }
+}
+
+impl Default for Lane {
+    fn default() -> Self {
+        Lane {
+            inset: PADDLE_WIDTH,
+            bottom: board_bottom(),
+            top: board_top(),
+        }
+    }
+}
+
+impl Arena {
+    /// Reads an arena from a file, making sure that it can be played in
+    pub fn load(path: impl AsRef<Path>) -> Result<Arena, ArenaError> {
+        let arena: Arena = ron::from_str(&fs::read_to_string(path)?)?;
+        arena.validate()?;
+        Ok(arena)
+    }
+
+    /// Checks that everything fits on the board and leaves the ball room to
+    /// be served from the center
+    pub fn validate(&self) -> Result<(), ArenaError> {
+        let board_height = board_top() - board_bottom();
+
+        if let Some(goal_height) = self.goal_height {
+            check_range("goal_height", goal_height, BALL_SIZE * 2.0, board_height)?;
+        }
+        self.left_lane.validate("left_lane")?;
+        self.right_lane.validate("right_lane")?;
+
+        let clearance = BALL_SIZE / 2.0 + WALL_THICKNESS / 2.0;
+        for (index, wall) in self.walls.iter().enumerate() {
+            let reason = if wall.start == wall.end {
+                "must have two different ends"
+            } else if !on_board(wall.start) || !on_board(wall.end) {
+                "must be on the board"
+            } else if closest_point_on_segment(Vec2::ZERO, wall.start, wall.end).length()
+                < clearance
+            {
+                "must leave room for the ball to be served"
+            } else {
+                continue;
+            };
+            return Err(ArenaError::invalid(
+                "walls",
+                format!("number {index} {reason}"),
+            ));
+        }
+
+        for (index, obstacle) in self.obstacles.iter().enumerate() {
+            let reach = obstacle.reach();
+            let reason = if obstacle.half_size.min_element() <= 0.0 {
+                "must have a size"
+            } else if obstacle.motion.is_some_and(|motion| motion.period <= 0.0) {
+                "must take some time to move"
+            } else if !on_board(obstacle.center - reach) || !on_board(obstacle.center + reach) {
+                "must stay on the board"
+            } else if penetration(
+                Vec2::ZERO,
+                Vec2::splat(BALL_SIZE / 2.0),
+                obstacle.center,
+                reach,
+            )
+            .is_some()
+            {
+                "must leave room for the ball to be served"
+            } else {
+                continue;
+            };
+            return Err(ArenaError::invalid(
+                "obstacles",
+                format!("number {index} {reason}"),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Where the paddle on `side` is free to move
+    pub fn lane(&self, side: Side) -> Lane {
+        match side {
+            Side::Left => self.left_lane,
+            Side::Right => self.right_lane,
+        }
+    }
+
+    /// The walls on either side of each goal's opening, if the goals are
+    /// narrower than the board
+    pub fn goal_posts(&self) -> Vec<Segment> {
+        let Some(goal_height) = self.goal_height else {
+            return Vec::new();
+        };
+        let middle = (board_bottom() + board_top()) / 2.0;
+        let opening_bottom = middle - goal_height / 2.0;
+        let opening_top = middle + goal_height / 2.0;
+
+        [-WINDOW_WIDTH / 2.0, WINDOW_WIDTH / 2.0]
+            .into_iter()
+            .flat_map(|x| {
+                [
+                    Segment {
+                        start: Vec2::new(x, board_bottom()),
+                        end: Vec2::new(x, opening_bottom),
+                    },
+                    Segment {
+                        start: Vec2::new(x, opening_top),
+                        end: Vec2::new(x, board_top()),
+                    },
+                ]
+            })
+            .filter(|post| post.start.y < post.end.y)
+            .collect()
+    }
+}
+
+impl Lane {
+    /// Where along the board the center of the paddle on `side` sits
+    pub fn x(&self, side: Side) -> f32 {
+        match side {
+            Side::Left => -WINDOW_WIDTH / 2.0 + self.inset,
+            Side::Right => WINDOW_WIDTH / 2.0 - self.inset,
+        }
+    }
+
+    /// Keeps the center of a paddle `height` tall within the lane. A paddle
+    /// too tall to move in the lane at all is held in the middle of it.
+    pub fn clamp(&self, y: f32, height: f32) -> f32 {
+        let y_min = self.bottom + height / 2.0;
+        let y_max = self.top - height / 2.0;
+        if y_min > y_max {
+            (self.bottom + self.top) / 2.0
+        } else {
+            y.clamp(y_min, y_max)
+        }
+    }
+
+    fn validate(&self, field: &'static str) -> Result<(), ArenaError> {
+        check_range(
+            field,
+            self.inset,
+            PADDLE_WIDTH / 2.0,
+            WINDOW_WIDTH / 2.0 - BALL_SIZE * 2.0,
+        )?;
+        if self.bottom < board_bottom() || self.top > board_top() {
+            return Err(ArenaError::invalid(
+                field,
+                format!(
+                    "must be between {} and {}, but was from {} to {}",
+                    board_bottom(),
+                    board_top(),
+                    self.bottom,
+                    self.top
+                ),
+            ));
+        }
+        if self.top - self.bottom < BALL_SIZE {
+            return Err(ArenaError::invalid(
+                field,
+                format!("must be at least {BALL_SIZE} tall"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Segment {
+    /// The transform that lays a sprite as long as the wall along it
+    pub fn transform(&self) -> Transform {
+        let direction = self.end - self.start;
+        Transform::from_translation(((self.start + self.end) / 2.0).extend(0.5))
+            .with_rotation(Quat::from_rotation_z(direction.y.atan2(direction.x)))
+    }
+
+    pub fn length(&self) -> f32 {
+        self.start.distance(self.end)
+    }
+}
+
+impl Obstacle {
+    /// Where the middle of the obstacle is once the match has been played
+    /// for `elapsed`
+    pub fn position_at(&self, elapsed: Duration) -> Vec2 {
+        match self.motion {
+            Some(motion) => {
+                let phase = elapsed.as_secs_f32() / motion.period * TAU;
+                self.center + motion.offset * phase.sin()
+            }
+            None => self.center,
+        }
+    }
+
+    /// Half the size of the whole of the area that the obstacle sweeps out
+    /// as it moves, around its `center`
+    pub fn reach(&self) -> Vec2 {
+        self.half_size + self.motion.map_or(Vec2::ZERO, |motion| motion.offset.abs())
+    }
+}
+
+// Bottom and top of the space between the bottom border and the scorebar
+fn board_bottom() -> f32 {
+    -WINDOW_HEIGHT / 2.0
+}
+
+fn board_top() -> f32 {
+    WINDOW_HEIGHT / 2.0 - UI_HEIGHT
+}
+
+fn on_board(point: Vec2) -> bool {
+    point.x.abs() <= WINDOW_WIDTH / 2.0 && (board_bottom()..=board_top()).contains(&point.y)
+}
+
+fn check_range(field: &'static str, value: f32, min: f32, max: f32) -> Result<(), ArenaError> {
+    if (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(ArenaError::invalid(
+            field,
+            format!("must be between {min} and {max}, but was {value}"),
+        ))
+    }
+}
+
+/// Spawns the walls, goal posts and obstacles of an arena, returning them to
+/// be torn down along with the match
+pub(crate) fn spawn_arena(commands: &mut Commands, arena: &Arena) -> Vec<Entity> {
+    let mut entities = Vec::new();
+
+    for wall in arena.walls.iter().chain(arena.goal_posts().iter()) {
+        entities.push(
+            commands
+                .spawn((TransformBundle::from_transform(wall.transform()), *wall))
+                .id(),
+        );
+    }
+
+    for obstacle in arena.obstacles.iter() {
+        let position = obstacle.position_at(Duration::ZERO);
+        entities.push(
+            commands
+                .spawn((
+                    TransformBundle::from_transform(Transform::from_translation(
+                        position.extend(0.5),
+                    )),
+                    Position::new(position),
+                    *obstacle,
+                ))
+                .id(),
+        );
+    }
+    entities
+}
+
+// Moving obstacles follow the match clock, so they are always in the same
+// place at the same point of a match
+fn move_obstacles(mut obstacle_query: Query<(&mut Position, &Obstacle)>, clock: Res<MatchClock>) {
+    for (mut position, obstacle) in obstacle_query.iter_mut() {
+        if obstacle.motion.is_some() {
+            position.current = obstacle.position_at(clock.elapsed);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ArenaError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Invalid { field: &'static str, reason: String },
+}
+
+impl ArenaError {
+    fn invalid(field: &'static str, reason: String) -> Self {
+        ArenaError::Invalid { field, reason }
+    }
+}
+
+impl fmt::Display for ArenaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArenaError::Io(error) => write!(f, "could not read the arena file: {error}"),
+            ArenaError::Parse(error) => write!(f, "the arena file is malformed: {error}"),
+            ArenaError::Invalid { field, reason } => write!(f, "`{field}` {reason}"),
+        }
+    }
+}
+
+impl Error for ArenaError {}
+
+impl From<io::Error> for ArenaError {
+    fn from(error: io::Error) -> Self {
+        ArenaError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for ArenaError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        ArenaError::Parse(error)
+    }
+}
@@ -1,162 +1,645 @@
-use crate::{AppState, UI_HEIGHT, WINDOW_HEIGHT, WINDOW_WIDTH};
-use rand::{thread_rng, Rng};
+use crate::arena::{Obstacle, Segment, WALL_THICKNESS};
+use crate::game::{GameData, MatchClock};
+use crate::physics::{
+    penetration, segment_penetration, sweep_aabb, sweep_segment, PhysicsSet, Position, SweepHit,
+};
+use crate::rng::GameRng;
+use crate::{match_in_progress, UI_HEIGHT, WINDOW_HEIGHT, WINDOW_WIDTH};
+use rand::Rng;
 use std::f32::consts::PI;
+use std::time::Duration;
 
-use super::paddle::{Paddle, PADDLE_HEIGHT, PADDLE_WIDTH};
-use super::score::{Score, ScoreChanged};
+use super::paddle::{Paddle, PaddleHeight, PaddleVelocity, ServeInput, Side, PADDLE_WIDTH};
+use super::power_up::ActivePowerUps;
+use super::rules::MatchRules;
+use super::score::PointScored;
 use bevy::prelude::*;
-use bevy::sprite::collide_aabb::collide;
 
-const BALL_SIZE: f32 = 30.;
-const BALL_DEFAULT_SPEED: f32 = 800.;
-const BALL_SERVE_MULTIPLIER: f32 = 0.65;
+pub const BALL_SIZE: f32 = 30.;
+// Upper limit on how many surfaces the ball may bounce off of in a single step
+const MAX_BOUNCES_PER_STEP: usize = 4;
+// How far the ball has to get from a paddle before touching it again counts as a new hit
+const CONTACT_MARGIN: f32 = 1.;
 
 pub struct BallPlugin;
 
 impl Plugin for BallPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (ball_movement, serve_on_score_change).run_if(in_state(AppState::Game)),
-        );
+        app.add_event::<PaddleHit>()
+            .add_event::<WallHit>()
+            .add_systems(
+                FixedUpdate,
+                (add_extra_balls, hold_serves, ball_movement)
+                    .chain()
+                    .in_set(PhysicsSet::Ball)
+                    .run_if(match_in_progress),
+            )
+            .add_systems(Update, log_paddle_hits.run_if(match_in_progress));
     }
 }
 
 #[derive(Component)]
 pub struct Ball {
     velocity: Vec2,
-    serve_left: bool,
+    // The side serving the ball, or that served it last
+    server: Side,
+    // Seconds left until the ball is served, while it is being held
+    countdown: Option<f32>,
+    // How many times the ball has been hit since it was last served
+    rally_hits: u32,
+    // The `rally_hits` that multiball last added balls up to
+    multiball_hits: u32,
+    // How long the ball has been in play since it was last served
+    in_play: Duration,
+    // The side whose paddle hit the ball last during this rally
+    last_hitter: Option<Side>,
+    // How quickly the ball turns as it goes, in radians a second
+    curve: f32,
+    // The paddle the ball is currently in contact with, if any
+    contact: Option<Entity>,
+}
+
+/// Marks the balls added by multiball on top of the one being served. They
+/// are never held for a serve, and leave the match once they score.
+#[derive(Component)]
+pub struct ExtraBall;
+
+/// Sent once each time a ball is struck by a paddle
+#[derive(Event, Clone, Copy)]
+pub struct PaddleHit {
+    /// The ball that was hit
+    pub ball: Entity,
+    /// The paddle that hit the ball
+    pub paddle: Entity,
+    /// Where the center of the ball was at the moment of impact
+    pub point: Vec2,
+    /// Direction the ball was sent off in, in radians
+    pub angle: f32,
 }
 
-impl Default for Ball {
-    fn default() -> Self {
-        let mut rng = thread_rng();
+/// Sent each time the ball bounces off of the top or bottom border, or off
+/// of a wall or obstacle of the arena
+#[derive(Event, Clone, Copy)]
+pub struct WallHit {
+    /// Where the center of the ball was at the moment of impact
+    pub point: Vec2,
+}
+
+impl Ball {
+    /// Creates a ball that is held in the center, waiting for the left side
+    /// to serve it
+    pub fn new(rules: &MatchRules) -> Self {
         Ball {
-            serve_left: true,
-            velocity: Vec2::from_angle(rng.gen_range((7.0 * PI) / 4.0..(9.0 * PI) / 4.0))
-                * (BALL_DEFAULT_SPEED * BALL_SERVE_MULTIPLIER),
+            velocity: Vec2::ZERO,
+            server: Side::Left,
+            countdown: Some(rules.serve_countdown),
+            rally_hits: 0,
+            multiball_hits: 0,
+            in_play: Duration::ZERO,
+            last_hitter: None,
+            curve: 0.0,
+            contact: None,
         }
     }
-}
 
-impl Ball {
-    fn serve(&mut self) {
-        let serve_modifier = if self.serve_left { 0.0 } else { PI };
-        self.serve_left = !self.serve_left;
+    /// Creates an extra ball in the center that is sent off at once, towards
+    /// either side
+    pub fn extra(rng: &mut GameRng, rules: &MatchRules) -> Self {
+        let mut ball = Ball::new(rules);
+        ball.server = if rng.gen() { Side::Left } else { Side::Right };
+        ball.launch(rng, rules);
+        ball
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        self.velocity
+    }
+
+    /// How many times the ball has been hit by the paddles since it was
+    /// last served
+    pub fn rally_hits(&self) -> u32 {
+        self.rally_hits
+    }
+
+    /// The speed that the ball leaves a paddle at. It picks up speed with
+    /// every hit of a rally, up to the limit set by the rules.
+    pub fn hit_speed(&self, rules: &MatchRules) -> f32 {
+        (rules.ball_speed + rules.ball_speed_increase * self.rally_hits as f32)
+            .min(rules.ball_max_speed)
+    }
+
+    /// The side whose paddle hit the ball last during this rally, which is
+    /// the one credited with any power-ups that the ball picks up
+    pub fn last_hitter(&self) -> Option<Side> {
+        self.last_hitter
+    }
+
+    /// The side serving the ball, or that served it last
+    pub fn server(&self) -> Side {
+        self.server
+    }
 
-        let mut rng = thread_rng();
+    /// Seconds left until the ball is served, while it is being held
+    pub fn countdown(&self) -> Option<f32> {
+        self.countdown
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.countdown.is_some()
+    }
+
+    // Holds the ball still until `server` serves it
+    fn hold(&mut self, server: Side, rules: &MatchRules) {
+        self.server = server;
+        self.countdown = Some(rules.serve_countdown);
+        self.velocity = Vec2::ZERO;
+        self.rally_hits = 0;
+        self.in_play = Duration::ZERO;
+        self.last_hitter = None;
+        self.curve = 0.0;
+        self.contact = None;
+    }
+
+    // Sends the ball off towards the side that isn't serving
+    fn launch(&mut self, rng: &mut GameRng, rules: &MatchRules) {
+        let serve_modifier = match self.server {
+            Side::Left => 0.0,
+            Side::Right => PI,
+        };
+        self.countdown = None;
         self.velocity =
             Vec2::from_angle(rng.gen_range((7.0 * PI) / 4.0..(9.0 * PI) / 4.0) + serve_modifier)
-                * (BALL_DEFAULT_SPEED * BALL_SERVE_MULTIPLIER);
+                * (rules.ball_speed * rules.ball_serve_multiplier);
     }
 }
 
+/// The lowest and highest that the center of the ball can go, between the
+/// bottom border and the scorebar
+pub fn ball_bounds() -> (f32, f32) {
+    let half_ball_size = BALL_SIZE / 2.0;
+    let vertical_border = WINDOW_HEIGHT / 2.0;
+    (
+        -vertical_border + half_ball_size,
+        vertical_border - UI_HEIGHT - half_ball_size,
+    )
+}
+
+// A paddle as the ball sees it when running into it
+#[derive(Clone, Copy)]
+struct Struck {
+    entity: Entity,
+    center: Vec2,
+    half_size: Vec2,
+    // How fast the paddle was moving upwards
+    velocity: f32,
+    side: Side,
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn ball_movement(
-    time: Res<Time>, // For movement calculations
-    mut ball_query: Query<(&mut Transform, &mut Ball)>,
+    fixed_time: Res<FixedTime>, // For movement calculations
+    rules: Res<MatchRules>,
+    mut ball_query: Query<(Entity, &mut Position, &mut Ball, Option<&ExtraBall>)>,
     // For hitbox calculations
-    paddle_query: Query<&Transform, (With<Paddle>, Without<Ball>)>,
-    // Manipulating the score
-    score_query: Query<&Score>,
-    // Sound effect upon bouncing off a wall
-    sound_query: Query<(), With<AudioSink>>,
-    asset_server: Res<AssetServer>,
+    paddle_query: Query<
+        (Entity, &Position, &PaddleVelocity, &PaddleHeight, &Side),
+        (With<Paddle>, Without<Ball>),
+    >,
+    wall_query: Query<&Segment>,
+    obstacle_query: Query<(&Position, &Obstacle), Without<Ball>>,
+    mut power_ups: ResMut<ActivePowerUps>,
+    mut point_event: EventWriter<PointScored>,
+    mut paddle_hit_event: EventWriter<PaddleHit>,
+    mut wall_hit_event: EventWriter<WallHit>,
+    mut game_data: ResMut<GameData>,
     mut commands: Commands,
-    mut score_event: EventWriter<ScoreChanged>,
 ) {
-    let score = score_query
-        .get_single()
-        .expect("Only one score object should exist at a time!");
+    // Power-ups can speed up or slow down every ball in play
+    let delta_seconds = fixed_time.period.as_secs_f32() * power_ups.ball_time_scale();
+    let paddles: Vec<Struck> = paddle_query
+        .iter()
+        .map(|(entity, position, velocity, height, side)| Struck {
+            entity,
+            center: position.current,
+            half_size: Vec2::new(PADDLE_WIDTH, height.0) / 2.0,
+            velocity: velocity.0,
+            side: *side,
+        })
+        .collect();
+    let walls: Vec<Segment> = wall_query.iter().copied().collect();
+    let obstacles: Vec<(Vec2, Vec2)> = obstacle_query
+        .iter()
+        .map(|(position, obstacle)| (position.current, obstacle.half_size))
+        .collect();
+    // The ball is kept a radius away from the line down the middle of a wall
+    let wall_radius = BALL_SIZE / 2.0 + WALL_THICKNESS / 2.0;
 
-    for (mut ball_transform, mut ball) in ball_query.iter_mut() {
-        let ball_translation = &mut ball_transform.translation;
+    for (ball_entity, mut ball_position, mut ball, extra_ball) in ball_query.iter_mut() {
+        if ball.is_held() {
+            continue;
+        }
+        ball.in_play += fixed_time.period;
 
         // Border collision handling
         let half_ball_size = BALL_SIZE / 2.0;
         let horizontal_border = WINDOW_WIDTH / 2.0;
-        let vertical_border = WINDOW_HEIGHT / 2.0;
         let x_min = -horizontal_border + half_ball_size;
         let x_max = horizontal_border - half_ball_size;
-        let y_min = -vertical_border + half_ball_size;
-        let y_max = vertical_border - UI_HEIGHT - half_ball_size;
-
-        // Check for collisions with the goals
-        if ball_translation.x < x_min {
-            ball.velocity.x *= -1.0;
-            score_event.send(ScoreChanged(Score {
-                right_score: score.right_score + 1,
-                left_score: score.left_score,
-            }));
-        } else if ball_translation.x > x_max {
-            ball.velocity.x *= -1.0;
-            score_event.send(ScoreChanged(Score {
-                right_score: score.right_score,
-                left_score: score.left_score + 1,
-            }));
+        let (y_min, y_max) = ball_bounds();
+
+        // A ball past either goal line is a point for the other side, unless
+        // the goal is shielded. It goes straight back to the center to be held
+        // for the next serve, or out of the match if it was an extra ball, so
+        // it can only ever score once.
+        let scorer = if ball_position.current.x < x_min {
+            Some(Side::Right)
+        } else if ball_position.current.x > x_max {
+            Some(Side::Left)
+        } else {
+            None
+        };
+        if let Some(scorer) = scorer {
+            if power_ups.use_shield(scorer.opponent()) {
+                ball.velocity.x *= -1.0;
+                ball_position.current.x = ball_position.current.x.clamp(x_min, x_max);
+                wall_hit_event.send(WallHit {
+                    point: ball_position.current,
+                });
+            } else {
+                point_event.send(PointScored {
+                    side: scorer,
+                    rally_hits: ball.rally_hits,
+                    duration: ball.in_play,
+                });
+                if extra_ball.is_some() {
+                    commands.entity(ball_entity).despawn();
+                    game_data.remove(ball_entity);
+                    continue;
+                }
+                ball_position.teleport(Vec2::ZERO);
+                let server = rules.serve_rule.next_server(ball.server, scorer);
+                ball.hold(server, &rules);
+                continue;
+            }
         }
+        let ball_translation = &mut ball_position.current;
+        let ball_half_size = Vec2::splat(half_ball_size);
 
-        let mut should_sound_play = false;
+        // A curving ball turns a little every step without changing speed
+        if ball.curve != 0.0 {
+            ball.velocity = Vec2::from_angle(ball.curve * delta_seconds).rotate(ball.velocity);
+        }
 
-        // Check for collisions with paddles
-        for paddle_transform in paddle_query.into_iter() {
-            if collide(
+        // A paddle may have moved on top of the ball, in which case there is
+        // nothing to sweep against. Push the ball back out of the paddle, and
+        // only count it as a hit if it isn't the paddle we are already touching
+        for paddle in paddles.iter() {
+            let Some(push) = penetration(
                 *ball_translation,
-                Vec2::splat(BALL_SIZE),
-                paddle_transform.translation,
-                Vec2::new(PADDLE_WIDTH, PADDLE_HEIGHT),
-            )
-            .is_some()
+                ball_half_size,
+                paddle.center,
+                paddle.half_size,
+            ) else {
+                continue;
+            };
+            *ball_translation += push;
+            ball_translation.y = ball_translation.y.clamp(y_min, y_max);
+
+            if ball.contact != Some(paddle.entity) {
+                hit_paddle(
+                    ball_entity,
+                    &mut ball,
+                    *ball_translation,
+                    paddle,
+                    power_ups.curve(paddle.side),
+                    &rules,
+                    &mut paddle_hit_event,
+                );
+            }
+        }
+
+        // Moving obstacles can run into the ball in the same way, and may then
+        // shove it up against a wall. Either way the ball is pushed back out,
+        // bouncing off if it was heading into what it is pushed out of.
+        for (center, half_size) in obstacles.iter() {
+            let Some(push) = penetration(*ball_translation, ball_half_size, *center, *half_size)
+            else {
+                continue;
+            };
+            push_ball_out(&mut ball, ball_translation, push, &mut wall_hit_event);
+            ball_translation.y = ball_translation.y.clamp(y_min, y_max);
+        }
+        for wall in walls.iter() {
+            if let Some(push) =
+                segment_penetration(*ball_translation, wall.start, wall.end, wall_radius)
             {
-                // Generate the new launch angle using the line between the center of the paddle
-                // and the ball, and setting that as the new speed of the ball
-                ball.velocity = Vec2::new(
-                    ball_translation.x - paddle_transform.translation.x,
-                    ball_translation.y - paddle_transform.translation.y,
-                )
-                .normalize()
-                    * BALL_DEFAULT_SPEED;
-                should_sound_play = true;
-                // Create the sound effect upon hitting a wall
+                push_ball_out(&mut ball, ball_translation, push, &mut wall_hit_event);
+            }
+        }
+
+        // Sweep the ball along its path for this step, bouncing off of whatever
+        // it reaches first and then carrying on with the time that remains
+        let mut remaining_seconds = delta_seconds;
+        for _ in 0..MAX_BOUNCES_PER_STEP {
+            let motion = ball.velocity * remaining_seconds;
+            let mut earliest: Option<(SweepHit, Option<&Struck>)> = None;
+
+            // Top and bottom borders
+            let wall_hit = if motion.y < 0.0 && ball_translation.y + motion.y < y_min {
+                Some(SweepHit {
+                    time: ((y_min - ball_translation.y) / motion.y).max(0.0),
+                    normal: Vec2::Y,
+                })
+            } else if motion.y > 0.0 && ball_translation.y + motion.y > y_max {
+                Some(SweepHit {
+                    time: ((y_max - ball_translation.y) / motion.y).max(0.0),
+                    normal: Vec2::NEG_Y,
+                })
+            } else {
+                None
+            };
+            if let Some(hit) = wall_hit {
+                earliest = Some((hit, None));
+            }
+
+            // Walls of the arena, at whatever angle they lie
+            for wall in walls.iter() {
+                if let Some(hit) =
+                    sweep_segment(*ball_translation, motion, wall.start, wall.end, wall_radius)
+                {
+                    if earliest.is_none_or(|(closest, _)| hit.time < closest.time) {
+                        earliest = Some((hit, None));
+                    }
+                }
+            }
+
+            // Obstacles and paddles, grown by the size of the ball so it can be
+            // swept as a point
+            for (center, half_size) in obstacles.iter() {
+                if let Some(hit) = sweep_aabb(
+                    *ball_translation,
+                    motion,
+                    *center,
+                    *half_size + ball_half_size,
+                ) {
+                    if earliest.is_none_or(|(closest, _)| hit.time < closest.time) {
+                        earliest = Some((hit, None));
+                    }
+                }
+            }
+            for paddle in paddles.iter() {
+                if let Some(hit) = sweep_aabb(
+                    *ball_translation,
+                    motion,
+                    paddle.center,
+                    paddle.half_size + ball_half_size,
+                ) {
+                    if earliest.is_none_or(|(closest, _)| hit.time < closest.time) {
+                        earliest = Some((hit, Some(paddle)));
+                    }
+                }
+            }
+
+            let Some((hit, paddle)) = earliest else {
+                // Nothing in the way, so move the rest of the distance
+                *ball_translation += motion;
+                break;
+            };
+
+            *ball_translation += motion * hit.time;
+            remaining_seconds *= 1.0 - hit.time;
+
+            match paddle {
+                // Still touching the paddle we last hit, so just slide off of it
+                Some(paddle) if ball.contact == Some(paddle.entity) => {
+                    ball.velocity = reflect(ball.velocity, hit.normal);
+                }
+                Some(paddle) => {
+                    hit_paddle(
+                        ball_entity,
+                        &mut ball,
+                        *ball_translation,
+                        paddle,
+                        power_ups.curve(paddle.side),
+                        &rules,
+                        &mut paddle_hit_event,
+                    );
+                }
+                None => {
+                    ball.velocity = reflect(ball.velocity, hit.normal);
+                    wall_hit_event.send(WallHit {
+                        point: *ball_translation,
+                    });
+                }
             }
         }
 
-        // Check for collisions with top/bottom borders
-        if ball_translation.y < y_min {
-            ball_translation.y = y_min;
-            ball.velocity.y *= -1.0;
-            should_sound_play = true;
-        } else if ball_translation.y > y_max {
-            ball_translation.y = y_max;
-            ball.velocity.y *= -1.0;
-            should_sound_play = true;
+        // Forget about the paddle once the ball has moved clear of it
+        if let Some(contact) = ball.contact {
+            let still_touching = paddles
+                .iter()
+                .find(|paddle| paddle.entity == contact)
+                .is_some_and(|paddle| {
+                    penetration(
+                        *ball_translation,
+                        ball_half_size + Vec2::splat(CONTACT_MARGIN),
+                        paddle.center,
+                        paddle.half_size,
+                    )
+                    .is_some()
+                });
+            if !still_touching {
+                ball.contact = None;
+            }
         }
+    }
+}
+
+// Mirrors a velocity in the surface with the given normal
+fn reflect(velocity: Vec2, normal: Vec2) -> Vec2 {
+    velocity - 2.0 * velocity.dot(normal) * normal
+}
 
-        // Begin to move the ball
-        ball_translation.x += ball.velocity.x * time.delta_seconds();
-        ball_translation.y += ball.velocity.y * time.delta_seconds();
-        // Play sound effects
-        if should_sound_play && sound_query.is_empty() {
-            commands.spawn(create_hit_sound(&asset_server));
+// Moves the ball out of something it has ended up inside of, bouncing it off
+// if it was heading further in
+fn push_ball_out(
+    ball: &mut Ball,
+    ball_translation: &mut Vec2,
+    push: Vec2,
+    wall_hit_event: &mut EventWriter<WallHit>,
+) {
+    *ball_translation += push;
+    let normal = push.normalize_or_zero();
+    if ball.velocity.dot(normal) < 0.0 {
+        ball.velocity = reflect(ball.velocity, normal);
+        wall_hit_event.send(WallHit {
+            point: *ball_translation,
+        });
+    }
+}
+
+// Launch the ball away from the paddle it has just hit, using the line between
+// the center of the paddle and the ball as the new direction of travel. A
+// moving paddle puts spin on the ball, which bends that direction along with
+// the paddle without changing how fast the ball goes. A paddle with the curve
+// power-up also sends the ball off curving back towards the middle of the
+// board, turning by `curve` radians a second.
+fn hit_paddle(
+    ball_entity: Entity,
+    ball: &mut Ball,
+    ball_translation: Vec2,
+    paddle: &Struck,
+    curve: f32,
+    rules: &MatchRules,
+    paddle_hit_event: &mut EventWriter<PaddleHit>,
+) {
+    let speed = ball.hit_speed(rules);
+    // Straight out of the paddle's face if the ball is right on its center
+    let away = match paddle.side {
+        Side::Left => Vec2::X,
+        Side::Right => Vec2::NEG_X,
+    };
+    let direction = (ball_translation - paddle.center).normalize_or_zero();
+    ball.velocity = if direction == Vec2::ZERO {
+        away
+    } else {
+        direction
+    } * speed;
+    let spin = paddle.velocity * rules.paddle_spin;
+    if spin != 0.0 {
+        ball.velocity = (ball.velocity + Vec2::new(0.0, spin)).normalize() * speed;
+    }
+    ball.curve = -curve * (ball.velocity.x * ball.velocity.y).signum();
+    ball.contact = Some(paddle.entity);
+    ball.last_hitter = Some(paddle.side);
+    ball.rally_hits += 1;
+
+    paddle_hit_event.send(PaddleHit {
+        ball: ball_entity,
+        paddle: paddle.entity,
+        point: ball_translation,
+        angle: ball.velocity.y.atan2(ball.velocity.x),
+    });
+}
+
+// Adds extra balls every so often, and whenever a rally goes on for long
+// enough, as long as there is room for them
+fn add_extra_balls(
+    mut commands: Commands,
+    mut ball_query: Query<&mut Ball>,
+    mut game_data: ResMut<GameData>,
+    mut rng: ResMut<GameRng>,
+    clock: Res<MatchClock>,
+    fixed_time: Res<FixedTime>,
+    rules: Res<MatchRules>,
+) {
+    let Some(multiball) = rules.multiball else {
+        return;
+    };
+
+    // A new ball for every `interval` of play that has gone by this step
+    let mut new_balls = 0;
+    if multiball.interval > 0.0 {
+        let interval = Duration::from_secs_f32(multiball.interval).as_nanos();
+        let before = clock.elapsed.saturating_sub(fixed_time.period).as_nanos();
+        if clock.elapsed.as_nanos() / interval > before / interval {
+            new_balls += 1;
+        }
+    }
+    // And one for every stretch of hits that a ball has reached since it was
+    // last looked at. A ball that has just been served starts over from 0.
+    for mut ball in ball_query.iter_mut() {
+        if multiball.rally_hits > 0 && ball.multiball_hits != ball.rally_hits {
+            new_balls += (ball.rally_hits / multiball.rally_hits)
+                .saturating_sub(ball.multiball_hits / multiball.rally_hits);
+            ball.multiball_hits = ball.rally_hits;
         }
     }
+
+    let room = (multiball.max_balls as usize).saturating_sub(ball_query.iter().count());
+    for _ in 0..(new_balls as usize).min(room) {
+        spawn_extra_ball(&mut commands, &mut game_data, &mut rng, &rules);
+    }
 }
 
-// Reset object position every time the score changes
-fn serve_on_score_change(
-    mut ball_query: Query<(&mut Transform, &mut Ball)>,
-    mut score_event: EventReader<ScoreChanged>,
+/// Sends an extra ball off from the center, to be torn down along with the
+/// match if it hasn't scored by then
+pub(crate) fn spawn_extra_ball(
+    commands: &mut Commands,
+    game_data: &mut GameData,
+    rng: &mut GameRng,
+    rules: &MatchRules,
 ) {
-    for _ in score_event.iter() {
-        for (mut transform, mut ball) in ball_query.iter_mut() {
-            *transform = Transform::IDENTITY;
-            ball.serve();
+    let entity = commands
+        .spawn((
+            TransformBundle::default(),
+            Ball::extra(rng, rules),
+            ExtraBall,
+            Position::default(),
+        ))
+        .id();
+    game_data.add(entity);
+}
+
+// Keeps held balls in place until they are served. A ball is served once its
+// countdown is over, unless it is waiting for a player serving by hand.
+fn hold_serves(
+    mut ball_query: Query<(&mut Position, &mut Ball)>,
+    paddle_query: Query<(&Position, &Side, &Paddle, &ServeInput), Without<Ball>>,
+    mut rng: ResMut<GameRng>,
+    rules: Res<MatchRules>,
+    fixed_time: Res<FixedTime>,
+) {
+    let delta_seconds = fixed_time.period.as_secs_f32();
+
+    for (mut position, mut ball) in ball_query.iter_mut() {
+        let Some(countdown) = ball.countdown else {
+            continue;
+        };
+        let countdown = (countdown - delta_seconds).max(0.0);
+        ball.countdown = Some(countdown);
+
+        // Players serving by hand carry the ball on their paddle until then
+        let by_hand = rules.manual_serve.then(|| {
+            paddle_query.iter().find(|(_, side, paddle, _)| {
+                **side == ball.server && matches!(paddle, Paddle::Player { .. })
+            })
+        });
+        let serve_input = match by_hand.flatten() {
+            Some((paddle_position, side, _, serve_input)) => {
+                position.current = serve_spot(paddle_position.current, *side);
+                serve_input.0
+            }
+            None => true,
+        };
+
+        if countdown <= 0.0 && serve_input {
+            ball.launch(&mut rng, &rules);
         }
     }
 }
 
-fn create_hit_sound(asset_server: &Res<AssetServer>) -> AudioBundle {
-    AudioBundle {
-        source: asset_server.load("sounds/hit_sound.wav"),
-        settings: PlaybackSettings::DESPAWN,
+// Just in front of the paddle, far enough away not to be touching it
+fn serve_spot(paddle_center: Vec2, side: Side) -> Vec2 {
+    let offset = PADDLE_WIDTH / 2.0 + BALL_SIZE / 2.0 + CONTACT_MARGIN * 2.0;
+    let direction = match side {
+        Side::Left => 1.0,
+        Side::Right => -1.0,
+    };
+    let (y_min, y_max) = ball_bounds();
+    Vec2::new(
+        paddle_center.x + offset * direction,
+        paddle_center.y.clamp(y_min, y_max),
+    )
+}
+
+fn log_paddle_hits(mut paddle_hit_event: EventReader<PaddleHit>) {
+    for hit in paddle_hit_event.iter() {
+        debug!(
+            "Paddle {:?} hit the ball at {} sending it off at {:.1} degrees",
+            hit.paddle,
+            hit.point,
+            hit.angle.to_degrees()
+        );
     }
 }
@@ -0,0 +1,476 @@
+//! Plays bots against each other without a window, as fast as the
+//! simulation allows, and reports how they fared.
+//!
+//! ```text
+//! pong-arena [OPTIONS] <LEFT> <RIGHT>
+//! ```
+//!
+//! Each side is one of `idle`, `computer`, `computer:<difficulty>`, or the
+//! address of an external bot as accepted by `PONG_LEFT_BOT`.
+use std::process::ExitCode;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_pong::ai::{ComputerController, Difficulty};
+use bevy_pong::arena::Arena;
+use bevy_pong::controller::{GameSnapshot, PaddleController, PaddleControllers};
+use bevy_pong::external::{BotAddress, ExternalBot};
+use bevy_pong::game::{GameData, MatchClock, MatchSetup};
+use bevy_pong::paddle::Side;
+use bevy_pong::physics::{PhysicsSet, PHYSICS_TIMESTEP};
+use bevy_pong::rules::MatchRules;
+use bevy_pong::score::{PointScored, Score};
+use bevy_pong::{AppState, PongCorePlugin};
+use serde::Serialize;
+
+const USAGE: &str = "\
+Usage: pong-arena [OPTIONS] <LEFT> <RIGHT>
+
+Plays LEFT against RIGHT, where each is one of:
+  idle                     never moves
+  computer[:<difficulty>]  the computer, at easy, normal, hard or impossible
+  <command>                a bot run as a child process
+  tcp:<address>            a bot listening on a TCP socket
+  unix:<path>              a bot listening on a Unix socket
+
+Options:
+  -n, --matches <N>        how many matches to play [default: 10]
+  -s, --seed <SEED>        seed of the first match, each one after uses the next [default: 0]
+  -f, --format <FORMAT>    table, json or csv [default: table]
+  -r, --rules <PATH>       settings file to read the match rules from
+  -a, --arena <PATH>       arena file to play the matches in [default: the classic board]
+  -t, --timeout <MS>       how long external bots get to answer each step [default: 10]
+  -h, --help               show this message";
+
+// How long external bots are waited on for each step. Matches here aren't
+// played in real time, so bots are given a chance to answer every step.
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(10);
+// Matches that go on for longer than this are abandoned without a winner
+const MAX_MATCH_TIME: Duration = Duration::from_secs(60 * 60);
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("{error}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let results: Vec<MatchResult> = (0..options.matches)
+        .map(|index| play_match(&options, options.seed.wrapping_add(index)))
+        .collect();
+    let summary = Summary::new(&results);
+
+    match options.format {
+        Format::Table => print_table(&options, &summary),
+        Format::Json => print_json(&options, &summary, &results),
+        Format::Csv => print_csv(&results),
+    }
+    ExitCode::SUCCESS
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+struct Options {
+    left: Contender,
+    right: Contender,
+    matches: u64,
+    seed: u64,
+    format: Format,
+    rules: MatchRules,
+    arena: Arena,
+    timeout: Duration,
+}
+
+impl Options {
+    // Returns `None` when only the help was asked for
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut contenders = Vec::new();
+        let mut matches = 10;
+        let mut seed = 0;
+        let mut format = Format::Table;
+        let mut rules = MatchRules::default();
+        let mut arena = Arena::default();
+        let mut timeout = DEFAULT_TIMEOUT;
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("`{arg}` needs a value"));
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-n" | "--matches" => matches = parse_number(&value()?)?,
+                "-s" | "--seed" => seed = parse_number(&value()?)?,
+                "-f" | "--format" => {
+                    format = match value()?.as_str() {
+                        "table" => Format::Table,
+                        "json" => Format::Json,
+                        "csv" => Format::Csv,
+                        other => return Err(format!("`{other}` is not a format")),
+                    }
+                }
+                "-r" | "--rules" => {
+                    let path = value()?;
+                    rules = MatchRules::load(&path)
+                        .map_err(|error| format!("Invalid rules in {path}: {error}"))?;
+                }
+                "-a" | "--arena" => {
+                    let path = value()?;
+                    arena = Arena::load(&path)
+                        .map_err(|error| format!("Invalid arena in {path}: {error}"))?;
+                }
+                "-t" | "--timeout" => timeout = Duration::from_millis(parse_number(&value()?)?),
+                _ if arg.starts_with('-') => return Err(format!("Unknown option `{arg}`")),
+                _ => contenders.push(arg.parse()?),
+            }
+        }
+
+        let [left, right]: [Contender; 2] = contenders
+            .try_into()
+            .map_err(|_| "Exactly two contenders are needed".to_string())?;
+        Ok(Some(Options {
+            left,
+            right,
+            matches,
+            seed,
+            format,
+            rules,
+            arena,
+            timeout,
+        }))
+    }
+}
+
+fn parse_number(value: &str) -> Result<u64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("`{value}` is not a whole number"))
+}
+
+/// Who plays on one of the sides
+#[derive(Clone)]
+enum Contender {
+    Idle,
+    Computer(Difficulty),
+    Bot(BotAddress),
+}
+
+impl std::str::FromStr for Contender {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.split_once(':') {
+            _ if name == "idle" => Ok(Contender::Idle),
+            _ if name == "computer" => Ok(Contender::Computer(Difficulty::default())),
+            Some(("computer", difficulty)) => Ok(Contender::Computer(difficulty.parse()?)),
+            _ => Ok(Contender::Bot(name.parse()?)),
+        }
+    }
+}
+
+impl std::fmt::Display for Contender {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Contender::Idle => write!(f, "idle"),
+            Contender::Computer(difficulty) => {
+                write!(f, "computer:{}", difficulty.to_string().to_lowercase())
+            }
+            Contender::Bot(address) => write!(f, "{address}"),
+        }
+    }
+}
+
+struct Idle;
+
+impl PaddleController for Idle {
+    fn movement(&mut self, _snapshot: &GameSnapshot) -> f32 {
+        0.0
+    }
+}
+
+fn register(
+    controllers: &mut PaddleControllers,
+    side: Side,
+    contender: &Contender,
+    seed: u64,
+    timeout: Duration,
+) {
+    match contender.clone() {
+        Contender::Idle => controllers.set(side, || Idle),
+        Contender::Computer(difficulty) => {
+            // Give each side its own way of misjudging the ball
+            let seed = match side {
+                Side::Left => seed,
+                Side::Right => !seed,
+            };
+            controllers.set(side, move || ComputerController::new(difficulty, seed));
+        }
+        Contender::Bot(address) => controllers.set(side, move || {
+            ExternalBot::new(address.clone()).with_timeout(timeout)
+        }),
+    }
+}
+
+#[derive(Serialize)]
+struct MatchResult {
+    seed: u64,
+    winner: Option<&'static str>,
+    /// Points in the set being played, for matches that are played in sets
+    left_score: u32,
+    right_score: u32,
+    /// Sets won, which stay at 0 unless the match is played in sets
+    left_sets: u32,
+    right_sets: u32,
+    /// Paddle hits in each point played
+    rallies: Vec<u32>,
+    /// Seconds each point lasted
+    point_seconds: Vec<f32>,
+}
+
+impl MatchResult {
+    fn average_rally(&self) -> f32 {
+        average(self.rallies.iter().map(|&hits| hits as f32))
+    }
+
+    fn average_point_seconds(&self) -> f32 {
+        average(self.point_seconds.iter().copied())
+    }
+}
+
+fn average(values: impl Iterator<Item = f32>) -> f32 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f32
+    }
+}
+
+// Follows each point of the match as it is played
+#[derive(Resource, Default)]
+struct PointLog {
+    rallies: Vec<u32>,
+    point_seconds: Vec<f32>,
+}
+
+fn log_points(mut log: ResMut<PointLog>, mut points: EventReader<PointScored>) {
+    for point in points.iter() {
+        log.rallies.push(point.rally_hits);
+        log.point_seconds.push(point.duration.as_secs_f32());
+    }
+}
+
+fn play_match(options: &Options, seed: u64) -> MatchResult {
+    let mut controllers = PaddleControllers::default();
+    register(
+        &mut controllers,
+        Side::Left,
+        &options.left,
+        seed,
+        options.timeout,
+    );
+    register(
+        &mut controllers,
+        Side::Right,
+        &options.right,
+        seed,
+        options.timeout,
+    );
+
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, PongCorePlugin))
+        // One simulation step for every update, without waiting on the clock
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            PHYSICS_TIMESTEP,
+        )))
+        .insert_resource(MatchSetup {
+            is_single_player: false,
+            seed: Some(seed),
+            arena: options.arena.clone(),
+            ..default()
+        })
+        .insert_resource(options.rules.clone())
+        .insert_resource(controllers)
+        .init_resource::<PointLog>()
+        .add_systems(
+            FixedUpdate,
+            log_points
+                .in_set(PhysicsSet::Victory)
+                .run_if(in_state(AppState::Game)),
+        );
+    app.update();
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Game);
+    app.update();
+
+    let mut score = Score::default();
+    loop {
+        app.update();
+        if *app.world.resource::<State<AppState>>().get() != AppState::Game {
+            break;
+        }
+        score = *app.world.query::<&Score>().single(&app.world);
+        if app.world.resource::<MatchClock>().elapsed > MAX_MATCH_TIME {
+            break;
+        }
+    }
+
+    let finished = *app.world.resource::<State<AppState>>().get() == AppState::End;
+    let winner = finished.then(|| {
+        if app.world.resource::<GameData>().is_left_win {
+            "left"
+        } else {
+            "right"
+        }
+    });
+    let log = app.world.remove_resource::<PointLog>().unwrap_or_default();
+    MatchResult {
+        seed,
+        winner,
+        left_score: score.left_score,
+        right_score: score.right_score,
+        left_sets: score.left_sets,
+        right_sets: score.right_sets,
+        rallies: log.rallies,
+        point_seconds: log.point_seconds,
+    }
+}
+
+#[derive(Serialize)]
+struct Summary {
+    matches: usize,
+    left_wins: usize,
+    right_wins: usize,
+    unfinished: usize,
+    left_win_rate: f32,
+    right_win_rate: f32,
+    average_rally: f32,
+    longest_rally: u32,
+    average_point_seconds: f32,
+    shortest_point_seconds: f32,
+    longest_point_seconds: f32,
+}
+
+impl Summary {
+    fn new(results: &[MatchResult]) -> Self {
+        let wins = |side| {
+            results
+                .iter()
+                .filter(|result| result.winner == Some(side))
+                .count()
+        };
+        let rate = |wins| {
+            if results.is_empty() {
+                0.0
+            } else {
+                wins as f32 / results.len() as f32
+            }
+        };
+        let rallies = results.iter().flat_map(|result| result.rallies.iter());
+        let point_seconds = || {
+            results
+                .iter()
+                .flat_map(|result| result.point_seconds.iter())
+        };
+        let (left_wins, right_wins) = (wins("left"), wins("right"));
+
+        Summary {
+            matches: results.len(),
+            left_wins,
+            right_wins,
+            unfinished: results.len() - left_wins - right_wins,
+            left_win_rate: rate(left_wins),
+            right_win_rate: rate(right_wins),
+            average_rally: average(rallies.clone().map(|&hits| hits as f32)),
+            longest_rally: rallies.copied().max().unwrap_or(0),
+            average_point_seconds: average(point_seconds().copied()),
+            shortest_point_seconds: point_seconds().copied().reduce(f32::min).unwrap_or(0.0),
+            longest_point_seconds: point_seconds().copied().reduce(f32::max).unwrap_or(0.0),
+        }
+    }
+}
+
+fn print_table(options: &Options, summary: &Summary) {
+    let left = options.left.to_string();
+    let right = options.right.to_string();
+    let width = left.len().max(right.len()).max(5);
+    println!(
+        "{} matches from seed {}{}",
+        summary.matches,
+        options.seed,
+        if summary.unfinished > 0 {
+            format!(", {} unfinished", summary.unfinished)
+        } else {
+            String::new()
+        }
+    );
+    println!();
+    println!("{:<width$}  {:>5}  {:>7}", "side", "wins", "rate");
+    for (name, wins, rate) in [
+        (left, summary.left_wins, summary.left_win_rate),
+        (right, summary.right_wins, summary.right_win_rate),
+    ] {
+        println!("{name:<width$}  {wins:>5}  {:>6.1}%", rate * 100.0);
+    }
+    println!();
+    println!(
+        "rally length    {:.2} hits on average, {} at most",
+        summary.average_rally, summary.longest_rally
+    );
+    println!(
+        "point duration  {:.2}s on average, {:.2}s to {:.2}s",
+        summary.average_point_seconds,
+        summary.shortest_point_seconds,
+        summary.longest_point_seconds
+    );
+}
+
+fn print_json(options: &Options, summary: &Summary, results: &[MatchResult]) {
+    #[derive(Serialize)]
+    struct Report<'a> {
+        left: String,
+        right: String,
+        summary: &'a Summary,
+        matches: &'a [MatchResult],
+    }
+    let report = Report {
+        left: options.left.to_string(),
+        right: options.right.to_string(),
+        summary,
+        matches: results,
+    };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report).expect("The report is always valid JSON")
+    );
+}
+
+fn print_csv(results: &[MatchResult]) {
+    println!(
+        "seed,winner,left_score,right_score,left_sets,right_sets,points,average_rally,average_point_seconds"
+    );
+    for result in results {
+        println!(
+            "{},{},{},{},{},{},{},{:.3},{:.3}",
+            result.seed,
+            result.winner.unwrap_or(""),
+            result.left_score,
+            result.right_score,
+            result.left_sets,
+            result.right_sets,
+            result.rallies.len(),
+            result.average_rally(),
+            result.average_point_seconds()
+        );
+    }
+}
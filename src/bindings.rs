@@ -0,0 +1,228 @@
+//! The keys that each player moves their paddle, serves and pauses the
+//! match with.
+//! They can be changed from the controls screen, and are kept in a file so
+//! that they are remembered the next time the game is started.
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::paddle::Side;
+
+pub struct BindingsPlugin;
+
+impl Plugin for BindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyBindings>();
+    }
+}
+
+/// Something a player can do with a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Up,
+    Down,
+    Serve,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 4] = [Action::Up, Action::Down, Action::Serve, Action::Pause];
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Action::Up => "Up",
+            Action::Down => "Down",
+            Action::Serve => "Serve",
+            Action::Pause => "Pause",
+        })
+    }
+}
+
+/// An action of the player on one side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Binding {
+    pub side: Side,
+    pub action: Action,
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let side = match self.side {
+            Side::Left => "Left",
+            Side::Right => "Right",
+        };
+        write!(f, "{side} {}", self.action)
+    }
+}
+
+/// The keys of one player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerKeys {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub serve: KeyCode,
+    pub pause: KeyCode,
+}
+
+/// The keys of both players. No key may be bound to more than one action.
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBindings {
+    pub left: PlayerKeys,
+    pub right: PlayerKeys,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            left: PlayerKeys {
+                up: KeyCode::W,
+                down: KeyCode::S,
+                serve: KeyCode::D,
+                pause: KeyCode::Escape,
+            },
+            right: PlayerKeys {
+                up: KeyCode::Up,
+                down: KeyCode::Down,
+                serve: KeyCode::Left,
+                pause: KeyCode::P,
+            },
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Reads the bindings from a RON file, refusing any that conflict
+    pub fn load(path: impl AsRef<Path>) -> Result<KeyBindings, BindingsError> {
+        let bindings: KeyBindings = ron::from_str(&fs::read_to_string(path)?)?;
+        if let Some((first, second)) = bindings.conflict() {
+            return Err(BindingsError::Conflict(first, second));
+        }
+        Ok(bindings)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), BindingsError> {
+        let path = path.as_ref();
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let pretty = ron::ser::PrettyConfig::default();
+        fs::write(path, ron::ser::to_string_pretty(self, pretty)?)?;
+        Ok(())
+    }
+
+    pub fn keys(&self, side: Side) -> &PlayerKeys {
+        match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        }
+    }
+
+    pub fn get(&self, binding: Binding) -> KeyCode {
+        let keys = self.keys(binding.side);
+        match binding.action {
+            Action::Up => keys.up,
+            Action::Down => keys.down,
+            Action::Serve => keys.serve,
+            Action::Pause => keys.pause,
+        }
+    }
+
+    /// Every action of both players
+    pub fn bindings() -> impl Iterator<Item = Binding> {
+        [Side::Left, Side::Right]
+            .into_iter()
+            .flat_map(|side| Action::ALL.map(|action| Binding { side, action }))
+    }
+
+    /// The action, other than `binding` itself, that `key` is already bound to
+    pub fn conflict_with(&self, binding: Binding, key: KeyCode) -> Option<Binding> {
+        Self::bindings().find(|&other| other != binding && self.get(other) == key)
+    }
+
+    /// Binds `key` to `binding`, unless it is already bound to another action.
+    /// The action holding on to the key is returned when it is refused.
+    pub fn rebind(&mut self, binding: Binding, key: KeyCode) -> Result<(), Binding> {
+        if let Some(other) = self.conflict_with(binding, key) {
+            return Err(other);
+        }
+        let keys = match binding.side {
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        };
+        match binding.action {
+            Action::Up => keys.up = key,
+            Action::Down => keys.down = key,
+            Action::Serve => keys.serve = key,
+            Action::Pause => keys.pause = key,
+        }
+        Ok(())
+    }
+
+    /// The first pair of actions found sharing a key
+    pub fn conflict(&self) -> Option<(Binding, Binding)> {
+        Self::bindings().find_map(|binding| {
+            self.conflict_with(binding, self.get(binding))
+                .map(|other| (binding, other))
+        })
+    }
+
+    /// The keys that either player pauses with
+    pub fn pause_keys(&self) -> [KeyCode; 2] {
+        [self.left.pause, self.right.pause]
+    }
+}
+
+#[derive(Debug)]
+pub enum BindingsError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    Conflict(Binding, Binding),
+}
+
+impl BindingsError {
+    /// Whether the bindings file simply doesn't exist
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, BindingsError::Io(error) if error.kind() == io::ErrorKind::NotFound)
+    }
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BindingsError::Io(error) => write!(f, "could not access the controls file: {error}"),
+            BindingsError::Parse(error) => write!(f, "the controls file is malformed: {error}"),
+            BindingsError::Serialize(error) => write!(f, "could not write the controls: {error}"),
+            BindingsError::Conflict(first, second) => {
+                write!(f, "{first} and {second} are bound to the same key")
+            }
+        }
+    }
+}
+
+impl Error for BindingsError {}
+
+impl From<io::Error> for BindingsError {
+    fn from(error: io::Error) -> Self {
+        BindingsError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for BindingsError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        BindingsError::Parse(error)
+    }
+}
+
+impl From<ron::Error> for BindingsError {
+    fn from(error: ron::Error) -> Self {
+        BindingsError::Serialize(error)
+    }
+}
@@ -0,0 +1,98 @@
+//! Paddles can be handed over to any type implementing `PaddleController`,
+//! which lets bots be written against the game without touching how the
+//! paddles are moved. Register one for a side through `PaddleControllers`
+//! and it takes the place of the keyboard or the computer on that side.
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::paddle::Side;
+use crate::rules::MatchRules;
+use crate::score::Score;
+
+/// Where the ball is and where it is heading
+#[derive(Debug, Clone, Copy)]
+pub struct BallState {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// Everything a controller gets to see of the match on a step. It is only a
+/// copy, so controllers can't change the match other than by moving their
+/// own paddle.
+#[derive(Debug, Clone, Copy)]
+pub struct GameSnapshot<'a> {
+    /// The side that the paddle being controlled defends
+    pub side: Side,
+    /// Center of the paddle being controlled
+    pub paddle: Vec2,
+    /// Center of the other paddle, if there is one
+    pub opponent: Option<Vec2>,
+    pub ball: BallState,
+    pub score: Score,
+    /// How long the match has been played for
+    pub elapsed: Duration,
+    pub rules: &'a MatchRules,
+    /// Length of the step being decided, in seconds
+    pub delta_seconds: f32,
+}
+
+pub trait PaddleController: Send + Sync + 'static {
+    /// Decides how the paddle moves on this step, from -1 for full speed
+    /// downwards to 1 for full speed upwards. Anything outside of that range
+    /// is clamped.
+    fn movement(&mut self, snapshot: &GameSnapshot) -> f32;
+}
+
+type ControllerFactory = Box<dyn Fn() -> Box<dyn PaddleController> + Send + Sync>;
+
+/// The controllers registered for each side. Every match builds fresh
+/// controllers from these, so that a bot never carries anything over from
+/// a previous match.
+#[derive(Resource, Default)]
+pub struct PaddleControllers {
+    left: Option<ControllerFactory>,
+    right: Option<ControllerFactory>,
+}
+
+impl PaddleControllers {
+    /// Hands the paddle on `side` over to the controllers built by `factory`
+    pub fn set<C: PaddleController>(
+        &mut self,
+        side: Side,
+        factory: impl Fn() -> C + Send + Sync + 'static,
+    ) {
+        *self.slot(side) = Some(Box::new(move || Box::new(factory())));
+    }
+
+    /// Gives the paddle on `side` back to the keyboard or the computer
+    pub fn clear(&mut self, side: Side) {
+        *self.slot(side) = None;
+    }
+
+    pub fn is_set(&self, side: Side) -> bool {
+        match side {
+            Side::Left => self.left.is_some(),
+            Side::Right => self.right.is_some(),
+        }
+    }
+
+    pub(crate) fn build(&self, side: Side) -> Option<Controller> {
+        let factory = match side {
+            Side::Left => self.left.as_ref(),
+            Side::Right => self.right.as_ref(),
+        }?;
+        Some(Controller(factory()))
+    }
+
+    fn slot(&mut self, side: Side) -> &mut Option<ControllerFactory> {
+        match side {
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        }
+    }
+}
+
+/// The controller in charge of a `Paddle::Controlled` paddle
+#[derive(Component)]
+pub struct Controller(pub(crate) Box<dyn PaddleController>);
@@ -0,0 +1,265 @@
+use bevy::prelude::*;
+
+use bevy_pong::bindings::{Action, Binding, KeyBindings};
+use bevy_pong::paddle::Side;
+use bevy_pong::AppState;
+
+use crate::{menu, MAIN_FONT};
+
+// File that the players' keys are kept in
+pub const CONTROLS_PATH: &str = "controls.ron";
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Controls), setup_controls)
+            .add_systems(
+                Update,
+                (
+                    controls_button,
+                    capture_key,
+                    update_key_text.run_if(
+                        resource_changed::<KeyBindings>()
+                            .or_else(resource_changed::<ControlsData>()),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Controls)),
+            )
+            .add_systems(OnExit(AppState::Controls), cleanup_controls);
+    }
+}
+
+// Holds the screen to be despawned, and the action waiting for a new key
+#[derive(Resource)]
+struct ControlsData {
+    controls_ui: Entity,
+    rebinding: Option<Binding>,
+}
+
+#[derive(Component)]
+enum ControlsButton {
+    Key(Binding),
+    Reset,
+    Back,
+}
+
+// Shows the key bound to an action
+#[derive(Component)]
+struct KeyText(Binding);
+
+// Tells the players what is going on
+#[derive(Component)]
+struct StatusText;
+
+fn setup_controls(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_style = |font_size: f32, color: Color| TextStyle {
+        font: asset_server.load(MAIN_FONT),
+        font_size,
+        color,
+    };
+    let mut ui_base = menu::create_ui_base();
+    ui_base.style.height = Val::Percent(100.0);
+    ui_base.style.row_gap = Val::Px(8.0);
+    let row = || NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(20.0),
+            ..default()
+        },
+        ..default()
+    };
+    // Wide enough to line the columns of the table up
+    let cell = |width: f32| NodeBundle {
+        style: Style {
+            width: Val::Px(width),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        ..default()
+    };
+
+    let controls_ui = commands
+        .spawn(ui_base)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Controls",
+                text_style(60.0, Color::WHITE),
+            ));
+            parent.spawn(row()).with_children(|parent| {
+                parent.spawn(cell(100.0));
+                for label in ["Left", "Right"] {
+                    parent.spawn(cell(150.0)).with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            label,
+                            text_style(32.0, Color::GRAY),
+                        ));
+                    });
+                }
+            });
+            for action in Action::ALL {
+                parent.spawn(row()).with_children(|parent| {
+                    parent.spawn(cell(100.0)).with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            action.to_string(),
+                            text_style(32.0, Color::WHITE),
+                        ));
+                    });
+                    for side in [Side::Left, Side::Right] {
+                        let binding = Binding { side, action };
+                        let mut key_button = menu::create_button();
+                        key_button.style.height = Val::Px(45.0);
+                        parent
+                            .spawn((key_button, ControlsButton::Key(binding)))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    TextBundle::from_section("", text_style(32.0, Color::BLACK)),
+                                    KeyText(binding),
+                                ));
+                            });
+                    }
+                });
+            }
+            parent.spawn((
+                TextBundle::from_section(
+                    "Click on a key to change it",
+                    text_style(24.0, Color::GRAY),
+                ),
+                StatusText,
+            ));
+            parent.spawn(row()).with_children(|parent| {
+                for (button, label) in [
+                    (ControlsButton::Reset, "Reset"),
+                    (ControlsButton::Back, "Back"),
+                ] {
+                    parent
+                        .spawn((menu::create_button(), button))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                label,
+                                text_style(40.0, Color::BLACK),
+                            ));
+                        });
+                }
+            });
+        })
+        .id();
+    commands.insert_resource(ControlsData {
+        controls_ui,
+        rebinding: None,
+    });
+}
+
+fn cleanup_controls(mut commands: Commands, controls_data: Res<ControlsData>) {
+    commands
+        .entity(controls_data.controls_ui)
+        .despawn_recursive();
+    commands.remove_resource::<ControlsData>();
+}
+
+fn controls_button(
+    mut interaction_query: Query<
+        (
+            &Interaction,
+            &mut BackgroundColor,
+            &mut BorderColor,
+            &ControlsButton,
+        ),
+        Changed<Interaction>,
+    >,
+    mut controls_data: ResMut<ControlsData>,
+    mut bindings: ResMut<KeyBindings>,
+    mut status_query: Query<&mut Text, With<StatusText>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, mut color, mut border_color, controls_button) in interaction_query.iter_mut()
+    {
+        match *interaction {
+            Interaction::Pressed => {
+                let status = match controls_button {
+                    // Clicking on the key being changed again leaves it be
+                    ControlsButton::Key(binding) if controls_data.rebinding == Some(*binding) => {
+                        controls_data.rebinding = None;
+                        "Click on a key to change it".to_string()
+                    }
+                    ControlsButton::Key(binding) => {
+                        controls_data.rebinding = Some(*binding);
+                        format!("Press the new key for {binding}")
+                    }
+                    ControlsButton::Reset => {
+                        controls_data.rebinding = None;
+                        *bindings = KeyBindings::default();
+                        save_bindings(&bindings);
+                        "Every key is back to its default".to_string()
+                    }
+                    ControlsButton::Back => {
+                        next_state.set(AppState::Menu);
+                        continue;
+                    }
+                };
+                for mut text in status_query.iter_mut() {
+                    text.sections[0].value = status.clone();
+                }
+            }
+            Interaction::Hovered => {
+                *color = Color::rgb_u8(96, 36, 78).into();
+                *border_color = Color::INDIGO.into();
+            }
+            Interaction::None => {
+                *color = Color::rgb_u8(153, 48, 122).into();
+                *border_color = Color::PURPLE.into();
+            }
+        }
+    }
+}
+
+// Binds the next key pressed to the action waiting for one, unless another
+// action already uses it
+fn capture_key(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut controls_data: ResMut<ControlsData>,
+    mut bindings: ResMut<KeyBindings>,
+    mut status_query: Query<&mut Text, With<StatusText>>,
+) {
+    let Some(binding) = controls_data.rebinding else {
+        return;
+    };
+    let Some(&key) = keyboard_input.get_just_pressed().next() else {
+        return;
+    };
+
+    let status = match bindings.rebind(binding, key) {
+        Ok(()) => {
+            controls_data.rebinding = None;
+            save_bindings(&bindings);
+            format!("{binding} is now {key:?}")
+        }
+        Err(other) => format!("{key:?} is already used for {other}, press another key"),
+    };
+    for mut text in status_query.iter_mut() {
+        text.sections[0].value = status.clone();
+    }
+}
+
+fn update_key_text(
+    controls_data: Res<ControlsData>,
+    bindings: Res<KeyBindings>,
+    mut text_query: Query<(&mut Text, &KeyText)>,
+) {
+    for (mut text, key_text) in text_query.iter_mut() {
+        text.sections[0].value = if controls_data.rebinding == Some(key_text.0) {
+            "...".to_string()
+        } else {
+            format!("{:?}", bindings.get(key_text.0))
+        };
+    }
+}
+
+// The keys are written out as soon as they change, so they are never lost
+fn save_bindings(bindings: &KeyBindings) {
+    if let Err(error) = bindings.save(CONTROLS_PATH) {
+        warn!("Could not save the controls to {CONTROLS_PATH}: {error}");
+    }
+}
@@ -0,0 +1,36 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use bevy_pong::pointer::Pointers;
+
+pub struct CursorPlugin;
+
+impl Plugin for CursorPlugin {
+    fn build(&self, app: &mut App) {
+        // The paddles read the pointers on the fixed timestep, which runs
+        // before `Update`, so keep them up to date straight after the input
+        app.add_systems(PreUpdate, track_pointers.after(InputSystem));
+    }
+}
+
+// Translates the mouse and touches from the window onto the board
+fn track_pointers(
+    mut pointers: ResMut<Pointers>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    touches: Res<Touches>,
+) {
+    let (Ok(window), Ok((camera, camera_transform))) =
+        (window_query.get_single(), camera_query.get_single())
+    else {
+        return;
+    };
+    let to_world = |position: Vec2| camera.viewport_to_world_2d(camera_transform, position);
+
+    pointers.mouse = window.cursor_position().and_then(to_world);
+    pointers.touches = touches
+        .iter()
+        .filter_map(|touch| to_world(touch.position()))
+        .collect();
+}
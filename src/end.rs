@@ -1,7 +1,9 @@
 use bevy::prelude::*;
 
-use crate::game::GameData;
-use crate::{menu, AppState, MAIN_FONT};
+use bevy_pong::game::GameData;
+use bevy_pong::AppState;
+
+use crate::{menu, MAIN_FONT};
 
 pub struct EndPlugin;
 
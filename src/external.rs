@@ -0,0 +1,341 @@
+//! Lets bots written in any language play by talking to them over a pipe or
+//! a socket. On every step the bot is sent one line of JSON describing the
+//! match, such as
+//!
+//! ```json
+//! {"tick":7,"side":"left","paddle":[-384.0,0.0],"opponent":[384.0,0.0],
+//!  "ball":{"position":[12.5,-3.0],"velocity":[520.0,-80.0]},
+//!  "score":{"left":0,"right":1},"elapsed":0.058}
+//! ```
+//!
+//! and is expected to answer with a line of `up`, `down` or `stay`. A bot
+//! that doesn't answer in time stays put for that step, so a slow bot can't
+//! hold up the match.
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::controller::{GameSnapshot, PaddleController};
+use crate::paddle::Side;
+
+/// How long a bot gets to answer before its paddle stays put for the step.
+/// Short enough for both paddles' bots to be waited on within a single step.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(3);
+// Messages that can be waiting to be written to a bot before any more are
+// dropped, so that a bot that stops reading can't hold up the match either
+const MAX_UNSENT_MESSAGES: usize = 64;
+
+/// Where to find a bot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BotAddress {
+    /// A program to run, followed by its arguments, which is spoken to over
+    /// its standard input and output
+    Command(Vec<String>),
+    /// A bot listening on a TCP socket, such as `127.0.0.1:4000`
+    Tcp(String),
+    /// A bot listening on a Unix socket
+    Unix(PathBuf),
+}
+
+impl FromStr for BotAddress {
+    type Err = String;
+
+    /// Parses `tcp:<address>`, `unix:<path>`, or otherwise a command line
+    fn from_str(address: &str) -> Result<Self, Self::Err> {
+        if let Some(address) = address.strip_prefix("tcp:") {
+            return Ok(BotAddress::Tcp(address.to_string()));
+        }
+        if let Some(path) = address.strip_prefix("unix:") {
+            return Ok(BotAddress::Unix(PathBuf::from(path)));
+        }
+        let command: Vec<String> = address.split_whitespace().map(String::from).collect();
+        if command.is_empty() {
+            return Err("no command was given for the bot".to_string());
+        }
+        Ok(BotAddress::Command(command))
+    }
+}
+
+impl fmt::Display for BotAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BotAddress::Command(command) => write!(f, "{}", command.join(" ")),
+            BotAddress::Tcp(address) => write!(f, "tcp:{address}"),
+            BotAddress::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// Moves a paddle as told by a bot running outside of the game
+pub struct ExternalBot {
+    address: BotAddress,
+    // Only ever used from `movement`, the lock just lets the bot be shared
+    connection: Option<Mutex<Connection>>,
+    timeout: Duration,
+    tick: u64,
+}
+
+struct Connection {
+    messages: SyncSender<String>,
+    replies: Receiver<String>,
+    // Kept so that the bot can be shut down along with the match
+    endpoint: Endpoint,
+}
+
+enum Endpoint {
+    Child(Child),
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixStream),
+}
+
+impl ExternalBot {
+    /// Starts or connects to the bot. A bot that can't be reached is
+    /// reported, and then leaves its paddle standing still.
+    pub fn new(address: BotAddress) -> Self {
+        let connection = match Connection::open(&address) {
+            Ok(connection) => Some(Mutex::new(connection)),
+            Err(error) => {
+                warn!("Could not reach the bot at {address}: {error}");
+                None
+            }
+        };
+        ExternalBot {
+            address,
+            connection,
+            timeout: DEFAULT_TIMEOUT,
+            tick: 0,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_some()
+    }
+
+    fn ask(&mut self, message: &BotMessage) -> io::Result<Option<BotCommand>> {
+        let Some(connection) = &mut self.connection else {
+            return Ok(None);
+        };
+        let connection = connection.get_mut().unwrap();
+
+        // Anything still waiting was meant for a step that has already gone
+        while connection.replies.try_recv().is_ok() {}
+
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        match connection.messages.try_send(line) {
+            // The bot is behind on its reading, so it misses this step
+            Ok(()) | Err(TrySendError::Full(_)) => {}
+            Err(TrySendError::Disconnected(_)) => return Err(io::ErrorKind::BrokenPipe.into()),
+        }
+
+        match connection.replies.recv_timeout(self.timeout) {
+            Ok(reply) => Ok(reply.parse().ok()),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+}
+
+impl PaddleController for ExternalBot {
+    fn movement(&mut self, snapshot: &GameSnapshot) -> f32 {
+        let message = BotMessage::new(self.tick, snapshot);
+        self.tick += 1;
+
+        match self.ask(&message) {
+            Ok(command) => command.map_or(0.0, BotCommand::movement),
+            Err(error) => {
+                warn!("Lost the bot at {}: {error}", self.address);
+                self.connection = None;
+                0.0
+            }
+        }
+    }
+}
+
+impl Connection {
+    fn open(address: &BotAddress) -> io::Result<Self> {
+        match address {
+            BotAddress::Command(command) => {
+                let mut child = Command::new(&command[0])
+                    .args(&command[1..])
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .spawn()?;
+                let writer = child.stdin.take().expect("The bot's input is piped");
+                let reader = child.stdout.take().expect("The bot's output is piped");
+                Ok(Connection {
+                    messages: write_messages(writer),
+                    replies: read_replies(reader),
+                    endpoint: Endpoint::Child(child),
+                })
+            }
+            BotAddress::Tcp(address) => {
+                let stream = TcpStream::connect(address)?;
+                stream.set_nodelay(true)?;
+                Ok(Connection {
+                    messages: write_messages(stream.try_clone()?),
+                    replies: read_replies(stream.try_clone()?),
+                    endpoint: Endpoint::Tcp(stream),
+                })
+            }
+            #[cfg(unix)]
+            BotAddress::Unix(path) => {
+                let stream = std::os::unix::net::UnixStream::connect(path)?;
+                Ok(Connection {
+                    messages: write_messages(stream.try_clone()?),
+                    replies: read_replies(stream.try_clone()?),
+                    endpoint: Endpoint::Unix(stream),
+                })
+            }
+            #[cfg(not(unix))]
+            BotAddress::Unix(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "unix sockets are not available on this platform",
+            )),
+        }
+    }
+}
+
+impl Drop for Connection {
+    // Closing our end of a socket isn't enough while the reading thread still
+    // holds a copy of it, so the whole connection is shut down instead
+    fn drop(&mut self) {
+        match &mut self.endpoint {
+            Endpoint::Child(child) => {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            Endpoint::Tcp(stream) => {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+            #[cfg(unix)]
+            Endpoint::Unix(stream) => {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+        }
+    }
+}
+
+// Reads the bot's answers on a thread of their own, so that waiting on them
+// can be given up on
+fn read_replies(reader: impl io::Read + Send + 'static) -> Receiver<String> {
+    let (sender, replies) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    replies
+}
+
+// Writes to the bot on a thread of its own, as writing blocks once a bot
+// stops reading and its end fills up
+fn write_messages(mut writer: impl Write + Send + 'static) -> SyncSender<String> {
+    let (messages, receiver) = mpsc::sync_channel::<String>(MAX_UNSENT_MESSAGES);
+    thread::spawn(move || {
+        for message in receiver {
+            if writer.write_all(message.as_bytes()).is_err() || writer.flush().is_err() {
+                break;
+            }
+        }
+    });
+    messages
+}
+
+/// An answer from a bot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BotCommand {
+    Up,
+    Down,
+    Stay,
+}
+
+impl BotCommand {
+    fn movement(self) -> f32 {
+        match self {
+            BotCommand::Up => 1.0,
+            BotCommand::Down => -1.0,
+            BotCommand::Stay => 0.0,
+        }
+    }
+}
+
+impl FromStr for BotCommand {
+    type Err = ();
+
+    fn from_str(reply: &str) -> Result<Self, Self::Err> {
+        match reply.trim() {
+            "up" => Ok(BotCommand::Up),
+            "down" => Ok(BotCommand::Down),
+            "stay" => Ok(BotCommand::Stay),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BotMessage {
+    tick: u64,
+    side: &'static str,
+    paddle: [f32; 2],
+    opponent: Option<[f32; 2]>,
+    ball: BallMessage,
+    score: ScoreMessage,
+    elapsed: f32,
+}
+
+#[derive(Serialize)]
+struct BallMessage {
+    position: [f32; 2],
+    velocity: [f32; 2],
+}
+
+#[derive(Serialize)]
+struct ScoreMessage {
+    left: u32,
+    right: u32,
+}
+
+impl BotMessage {
+    fn new(tick: u64, snapshot: &GameSnapshot) -> Self {
+        BotMessage {
+            tick,
+            side: match snapshot.side {
+                Side::Left => "left",
+                Side::Right => "right",
+            },
+            paddle: snapshot.paddle.to_array(),
+            opponent: snapshot.opponent.map(|opponent| opponent.to_array()),
+            ball: BallMessage {
+                position: snapshot.ball.position.to_array(),
+                velocity: snapshot.ball.velocity.to_array(),
+            },
+            score: ScoreMessage {
+                left: snapshot.score.left_score,
+                right: snapshot.score.right_score,
+            },
+            elapsed: snapshot.elapsed.as_secs_f32(),
+        }
+    }
+}
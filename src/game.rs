@@ -1,24 +1,75 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
+use crate::ai::{ComputerMind, Difficulty};
+use crate::arena::{self, Arena};
 use crate::ball::Ball;
-use crate::menu::MenuData;
-use crate::paddle::{Paddle, PADDLE_WIDTH};
-use crate::score::{Score, ScoreChanged};
-use crate::{AppState, WINDOW_WIDTH};
-
-// Amount of points needed for one side to win
-const VICTORY_POINT_REQ: u32 = 10;
+use crate::bindings::KeyBindings;
+use crate::controller::PaddleControllers;
+use crate::paddle::{Paddle, PaddleHeight, PaddleInput, PaddleVelocity, ServeInput, Side};
+use crate::physics::{PhysicsSet, Position};
+use crate::pointer::Pointer;
+use crate::power_up::ActivePowerUps;
+use crate::replay::ReplayPlayback;
+use crate::rng::GameRng;
+use crate::rules::MatchRules;
+use crate::score::Score;
+use crate::{match_in_progress, AppState};
 
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Game), setup_game)
-            .add_systems(Update, check_for_victory.run_if(in_state(AppState::Game)))
-            .add_systems(OnExit(AppState::Game), cleanup_game);
+        app.init_resource::<MatchSetup>()
+            .init_resource::<MatchRules>()
+            .init_resource::<PaddleControllers>()
+            .add_systems(OnEnter(AppState::Game), setup_game)
+            .add_systems(OnEnter(AppState::Replay), setup_game)
+            .add_systems(
+                FixedUpdate,
+                (
+                    tick_match_clock.in_set(PhysicsSet::Prepare),
+                    check_for_victory.in_set(PhysicsSet::Victory),
+                )
+                    .run_if(match_in_progress),
+            )
+            .add_systems(OnExit(AppState::Game), cleanup_game)
+            .add_systems(OnExit(AppState::Replay), cleanup_game)
+            .add_systems(OnEnter(AppState::Menu), discard_game_data);
     }
 }
 
+/// How the next match should be played. Chosen on the menu before
+/// entering `AppState::Game`, and stored in replays of the match.
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+pub struct MatchSetup {
+    pub is_single_player: bool,
+    /// How well the computer plays in single player matches
+    pub difficulty: Difficulty,
+    /// Seed for all of the match's randomness. A new one is picked at
+    /// random when this is left empty.
+    pub seed: Option<u64>,
+    /// Pointers that the players' paddles follow on each side, on top of
+    /// the keys
+    #[serde(default)]
+    pub left_pointer: Option<Pointer>,
+    #[serde(default)]
+    pub right_pointer: Option<Pointer>,
+    /// Whether each side was moved by a registered controller. Filled in
+    /// when the match is recorded, so that its replay sets the paddles up
+    /// the same way without the controllers.
+    #[serde(default)]
+    pub left_controlled: bool,
+    #[serde(default)]
+    pub right_controlled: bool,
+    /// The board that the match is played on
+    #[serde(default)]
+    pub arena: Arena,
+}
+
 // Holds assets to be despawned as well as passing
 // victory data to the next state.
 #[derive(Resource)]
@@ -27,6 +78,13 @@ pub struct GameData {
     pub is_left_win: bool,
 }
 
+/// How long the current match has been played for. Counted in simulation
+/// steps rather than real time, so that it is the same when replayed.
+#[derive(Resource, Default)]
+pub struct MatchClock {
+    pub elapsed: Duration,
+}
+
 impl GameData {
     fn new(game_entities: Vec<Entity>) -> Self {
         GameData {
@@ -34,74 +92,178 @@ impl GameData {
             is_left_win: false,
         }
     }
+
+    /// Has an entity spawned partway through the match torn down along with
+    /// the rest of it
+    pub(crate) fn add(&mut self, entity: Entity) {
+        self.game_entities.push(entity);
+    }
+
+    /// Forgets about an entity that has been despawned before the match is over
+    pub(crate) fn remove(&mut self, entity: Entity) {
+        self.game_entities
+            .retain(|&game_entity| game_entity != entity);
+    }
 }
 
-fn setup_game(mut commands: Commands, asset_server: Res<AssetServer>, menu_data: Res<MenuData>) {
+pub(crate) fn setup_game(
+    mut commands: Commands,
+    match_setup: Res<MatchSetup>,
+    playback: Option<Res<ReplayPlayback>>,
+    rules: Res<MatchRules>,
+    controllers: Res<PaddleControllers>,
+    bindings: Res<KeyBindings>,
+) {
+    // Registered controllers take over their side, except in replays where
+    // the recorded inputs move the paddles instead, and the paddles are only
+    // marked as controlled so that they serve as they did
+    let is_replay = playback.is_some();
+    let controller = |side| (!is_replay).then(|| controllers.build(side)).flatten();
+
+    // Replays are set up exactly as the recorded match was
+    let match_setup = match &playback {
+        Some(playback) => playback.replay().setup(),
+        None => &*match_setup,
+    };
+    let mut rng = match match_setup.seed {
+        Some(seed) => GameRng::from_seed(seed),
+        None => GameRng::from_entropy(),
+    };
+    info!("Starting match with seed {}", rng.seed());
+
+    // Always drawn, so that the rest of the match's randomness doesn't
+    // depend on who is playing
+    let mind_seed = rng.gen();
+
+    let left_lane = match_setup.arena.lane(Side::Left);
+    let right_lane = match_setup.arena.lane(Side::Right);
+    // Paddles start level with the ball, as far as their lanes allow
+    let left_paddle = Vec2::new(
+        left_lane.x(Side::Left),
+        left_lane.clamp(0.0, rules.paddle_height),
+    );
+    let right_paddle = Vec2::new(
+        right_lane.x(Side::Right),
+        right_lane.clamp(0.0, rules.paddle_height),
+    );
+
     // Create a vector that stores all the spawned entities for teardown later
-    let entities = vec![
+    let mut entities = vec![
         // Spawn the ball
         commands
             .spawn((
-                SpriteBundle {
-                    texture: asset_server.load("sprites/Ball.png"),
-                    ..default()
-                },
-                Ball::default(),
+                TransformBundle::default(),
+                Ball::new(&rules),
+                Position::default(),
             ))
             .id(),
         // Spawn the left-most paddle
-        commands
-            .spawn((
-                SpriteBundle {
-                    texture: asset_server.load("sprites/Computer.png"),
-                    transform: Transform::from_xyz((-WINDOW_WIDTH / 2.0) + PADDLE_WIDTH, 0.0, 1.0),
-                    ..default()
-                },
-                if menu_data.is_single_player {
-                    Paddle::Computer
-                } else {
-                    Paddle::Player {
-                        input_up: KeyCode::W,
-                        input_down: KeyCode::S,
-                    }
-                },
-            ))
-            .id(),
+        {
+            let mut left_paddle = commands.spawn((
+                TransformBundle::from_transform(Transform::from_translation(
+                    left_paddle.extend(1.0),
+                )),
+                Position::new(left_paddle),
+                Side::Left,
+                PaddleInput::default(),
+                ServeInput::default(),
+                PaddleVelocity::default(),
+                PaddleHeight(rules.paddle_height),
+                left_lane,
+            ));
+            if let Some(controller) = controller(Side::Left) {
+                left_paddle.insert((Paddle::Controlled, controller));
+            } else if is_replay && match_setup.left_controlled {
+                left_paddle.insert(Paddle::Controlled);
+            } else if match_setup.is_single_player {
+                left_paddle.insert((
+                    Paddle::Computer {
+                        skill: match_setup.difficulty.skill(),
+                    },
+                    ComputerMind::new(mind_seed),
+                ));
+            } else {
+                left_paddle.insert(Paddle::Player {
+                    input_up: bindings.left.up,
+                    input_down: bindings.left.down,
+                    input_serve: bindings.left.serve,
+                    pointer: match_setup.left_pointer,
+                });
+            }
+            left_paddle.id()
+        },
         // Spawn the right-most paddle
-        commands
-            .spawn((
-                SpriteBundle {
-                    texture: asset_server.load("sprites/Player.png"),
-                    transform: Transform::from_xyz((WINDOW_WIDTH / 2.0) - PADDLE_WIDTH, 0.0, 1.0),
-                    ..default()
-                },
-                Paddle::Player {
-                    input_up: KeyCode::Up,
-                    input_down: KeyCode::Down,
-                },
-            ))
-            .id(),
+        {
+            let mut right_paddle = commands.spawn((
+                TransformBundle::from_transform(Transform::from_translation(
+                    right_paddle.extend(1.0),
+                )),
+                Position::new(right_paddle),
+                Side::Right,
+                PaddleInput::default(),
+                ServeInput::default(),
+                PaddleVelocity::default(),
+                PaddleHeight(rules.paddle_height),
+                right_lane,
+            ));
+            if let Some(controller) = controller(Side::Right) {
+                right_paddle.insert((Paddle::Controlled, controller));
+            } else if is_replay && match_setup.right_controlled {
+                right_paddle.insert(Paddle::Controlled);
+            } else {
+                right_paddle.insert(Paddle::Player {
+                    input_up: bindings.right.up,
+                    input_down: bindings.right.down,
+                    input_serve: bindings.right.serve,
+                    pointer: match_setup.right_pointer,
+                });
+            }
+            right_paddle.id()
+        },
         // Initialize a score of 0,0
         commands.spawn(Score::default()).id(),
     ];
+    entities.extend(arena::spawn_arena(&mut commands, &match_setup.arena));
 
-    commands.remove_resource::<MenuData>();
     commands.insert_resource(GameData::new(entities));
+    commands.insert_resource(MatchClock::default());
+    commands.insert_resource(ActivePowerUps::default());
+    commands.insert_resource(rng);
+}
+
+fn tick_match_clock(mut clock: ResMut<MatchClock>, fixed_time: Res<FixedTime>) {
+    clock.elapsed += fixed_time.period;
 }
 
 fn check_for_victory(
-    mut change_events: EventReader<ScoreChanged>,
-    mut next_state: ResMut<NextState<AppState>>,
+    mut score_query: Query<&mut Score>,
+    clock: Res<MatchClock>,
+    rules: Res<MatchRules>,
     mut game_data: ResMut<GameData>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
 ) {
-    for event in change_events.iter() {
-        if event.0.right_score >= VICTORY_POINT_REQ {
-            game_data.is_left_win = false;
-            next_state.set(AppState::End);
-        } else if event.0.left_score >= VICTORY_POINT_REQ {
-            game_data.is_left_win = true;
-            next_state.set(AppState::End);
+    let mut score = score_query
+        .get_single_mut()
+        .expect("Only one score object should exist at a time!");
+
+    // Start the next set once one side has taken this one
+    if let Some(set_winner) = rules.victory.set_winner(&score) {
+        match set_winner {
+            Side::Left => score.left_sets += 1,
+            Side::Right => score.right_sets += 1,
         }
+        score.left_score = 0;
+        score.right_score = 0;
+    }
+
+    let Some(winner) = rules.victory.winner(&score, clock.elapsed) else {
+        return;
+    };
+    game_data.is_left_win = winner == Side::Left;
+    // Replays carry on until their recording runs out
+    if *state.get() == AppState::Game {
+        next_state.set(AppState::End);
     }
 }
 
@@ -110,3 +272,24 @@ fn cleanup_game(mut commands: Commands, game_data: Res<GameData>) {
         commands.entity(*entitiy).despawn();
     }
 }
+
+// Matches that are abandoned never reach the end screen, which would
+// otherwise be the one to remove their data
+fn discard_game_data(mut commands: Commands) {
+    commands.remove_resource::<GameData>();
+}
+
+/// Tears down the match in progress and sets up a fresh one in its place,
+/// starting again from the beginning of the seed
+pub fn restart_match(world: &mut World) {
+    run_system_now(world, cleanup_game);
+    run_system_now(world, setup_game);
+}
+
+// Runs a system to completion outside of any schedule, including its commands
+fn run_system_now<Marker>(world: &mut World, system: impl IntoSystem<(), (), Marker>) {
+    let mut system = IntoSystem::into_system(system);
+    system.initialize(world);
+    system.run((), world);
+    system.apply_deferred(world);
+}
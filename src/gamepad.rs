@@ -0,0 +1,152 @@
+//! Gamepads can play alongside the keyboard. A gamepad joins by pressing any
+//! of its buttons, which hands it the first free side, and leaves again when
+//! it is unplugged. The right side is handed out first, since that is the
+//! side played in single player matches.
+use bevy::ecs::system::SystemParam;
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use bevy::prelude::*;
+
+use crate::paddle::Side;
+
+pub struct GamepadControlPlugin;
+
+impl Plugin for GamepadControlPlugin {
+    fn build(&self, app: &mut App) {
+        // Gamepads are not available without a window either, so make sure
+        // the resources exist for headless matches
+        app.init_resource::<Input<GamepadButton>>()
+            .init_resource::<Axis<GamepadAxis>>()
+            .add_event::<GamepadConnectionEvent>()
+            .init_resource::<GamepadAssignments>()
+            .add_systems(Update, (release_unplugged_gamepads, join_gamepads).chain());
+    }
+}
+
+/// Which gamepad, if any, plays on each side
+#[derive(Resource, Default, Debug)]
+pub struct GamepadAssignments {
+    pub left: Option<Gamepad>,
+    pub right: Option<Gamepad>,
+}
+
+impl GamepadAssignments {
+    pub fn get(&self, side: Side) -> Option<Gamepad> {
+        match side {
+            Side::Left => self.left,
+            Side::Right => self.right,
+        }
+    }
+
+    /// The side that a gamepad plays on
+    pub fn side_of(&self, gamepad: Gamepad) -> Option<Side> {
+        [Side::Left, Side::Right]
+            .into_iter()
+            .find(|&side| self.get(side) == Some(gamepad))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.left.is_none() && self.right.is_none()
+    }
+
+    fn slot(&mut self, side: Side) -> &mut Option<Gamepad> {
+        match side {
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        }
+    }
+}
+
+/// Reads how the gamepads want the paddles to move
+#[derive(SystemParam)]
+pub struct GamepadInput<'w> {
+    assignments: Res<'w, GamepadAssignments>,
+    buttons: Res<'w, Input<GamepadButton>>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+impl GamepadInput<'_> {
+    /// How the gamepad on `side` wants its paddle to move, from -1 to 1. The
+    /// stick moves the paddle in proportion to how far it is pushed, and the
+    /// D-pad moves it at full speed.
+    pub fn movement(&self, side: Side) -> f32 {
+        let Some(gamepad) = self.assignments.get(side) else {
+            return 0.0;
+        };
+
+        let stick = self
+            .axes
+            .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+            .unwrap_or(0.0);
+        if stick != 0.0 {
+            return stick.clamp(-1.0, 1.0);
+        }
+
+        let mut direction = 0.0;
+        if self
+            .buttons
+            .pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadUp))
+        {
+            direction += 1.0;
+        }
+        if self
+            .buttons
+            .pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadDown))
+        {
+            direction -= 1.0;
+        }
+        direction
+    }
+
+    /// Whether the gamepad on `side` is holding down its serve button
+    pub fn serve(&self, side: Side) -> bool {
+        self.assignments.get(side).is_some_and(|gamepad| {
+            self.buttons
+                .pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
+        })
+    }
+
+    /// Whether a gamepad that has joined just pressed `button_type`
+    pub fn just_pressed(&self, button_type: GamepadButtonType) -> bool {
+        [Side::Left, Side::Right]
+            .into_iter()
+            .filter_map(|side| self.assignments.get(side))
+            .any(|gamepad| {
+                self.buttons
+                    .just_pressed(GamepadButton::new(gamepad, button_type))
+            })
+    }
+}
+
+// Any button pressed on a gamepad that hasn't joined yet claims the first
+// side that is free
+fn join_gamepads(mut assignments: ResMut<GamepadAssignments>, buttons: Res<Input<GamepadButton>>) {
+    for button in buttons.get_just_pressed() {
+        let gamepad = button.gamepad;
+        if assignments.side_of(gamepad).is_some() {
+            continue;
+        }
+        let free_side = [Side::Right, Side::Left]
+            .into_iter()
+            .find(|&side| assignments.get(side).is_none());
+        if let Some(side) = free_side {
+            info!("Gamepad {} joined on the {side:?} side", gamepad.id);
+            *assignments.slot(side) = Some(gamepad);
+        }
+    }
+}
+
+// Unplugging a gamepad frees its side up for the keyboard or another gamepad
+fn release_unplugged_gamepads(
+    mut assignments: ResMut<GamepadAssignments>,
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+) {
+    for event in connection_events.iter() {
+        if event.connection != GamepadConnection::Disconnected {
+            continue;
+        }
+        if let Some(side) = assignments.side_of(event.gamepad) {
+            info!("Gamepad {} left the {side:?} side", event.gamepad.id);
+            *assignments.slot(side) = None;
+        }
+    }
+}
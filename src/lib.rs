@@ -0,0 +1,122 @@
+//! The rules of Pong, free of any rendering, audio or windowing so that
+//! matches can be simulated headlessly under `MinimalPlugins`. The game
+//! binary builds its screens, sprites and sounds on top of this library.
+use bevy::prelude::*;
+
+pub mod ai;
+
+pub mod arena;
+use arena::ArenaPlugin;
+
+pub mod ball;
+use ball::BallPlugin;
+
+pub mod bindings;
+use bindings::BindingsPlugin;
+
+pub mod controller;
+
+pub mod external;
+
+pub mod game;
+use game::GamePlugin;
+
+pub mod gamepad;
+use gamepad::GamepadControlPlugin;
+
+pub mod paddle;
+use paddle::PaddlePlugin;
+
+pub mod pause;
+use pause::{PausePlugin, PauseState};
+
+pub mod physics;
+use physics::PhysicsPlugin;
+
+pub mod pointer;
+use pointer::PointerPlugin;
+
+pub mod power_up;
+use power_up::PowerUpPlugin;
+
+pub mod replay;
+use replay::{ReplayPlayback, ReplayPlugin};
+
+pub mod rng;
+
+pub mod rules;
+
+pub mod score;
+use score::ScorePlugin;
+
+// Consts to define the resolution of the game window in pixels
+pub const WINDOW_WIDTH: f32 = 802.;
+pub const WINDOW_HEIGHT: f32 = 455.;
+// Defines the pixel height of the top UI Scorebar
+pub const UI_HEIGHT: f32 = 47.;
+
+/// State management Enum to be used throughout the entire project.
+/// Each state corresponds to a different screen in the game as well
+/// as a different module in code.
+///
+/// - **Menu** refers to the Start up menu which displays the logo and
+///   prompts the player to select one or two players
+/// - **Game** refers to the actual game itself, and loads both the
+///   *game* module and it's respective components as well as the *ui* module
+/// - **End** refers to the simple end screen which loops back into the *Menu*
+///   state
+/// - **Replay** plays back a recorded match through the same systems as
+///   the *Game*, and returns to the *Menu* once the viewer is done
+/// - **Controls** lets the players change their keys, and returns to the
+///   *Menu*
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum AppState {
+    #[default]
+    Menu,
+    Game,
+    End,
+    Replay,
+    Controls,
+}
+
+/// Run condition for the systems that simulate a match. A match is simulated
+/// while it is being played and not paused, and while a replay still has
+/// inputs left to play.
+pub fn match_in_progress(
+    state: Res<State<AppState>>,
+    pause_state: Res<State<PauseState>>,
+    playback: Option<Res<ReplayPlayback>>,
+) -> bool {
+    match state.get() {
+        AppState::Game => *pause_state.get() == PauseState::Running,
+        AppState::Replay => playback.is_some_and(|playback| !playback.is_finished()),
+        AppState::Menu | AppState::End | AppState::Controls => false,
+    }
+}
+
+/// Everything needed to play a match without a window. Only requires the
+/// plugins found in `MinimalPlugins` to be present.
+pub struct PongCorePlugin;
+
+impl Plugin for PongCorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<AppState>().add_plugins((
+            // Runs the ball and paddles on a fixed timestep
+            PhysicsPlugin,
+            // Moves the obstacles of the arena being played in
+            ArenaPlugin,
+            // Plugins that are used during the actual game itself
+            // These plugins only run on the 'AppState::Game'
+            BallPlugin,
+            PaddlePlugin,
+            BindingsPlugin,
+            GamepadControlPlugin,
+            PointerPlugin,
+            PowerUpPlugin,
+            ScorePlugin,
+            GamePlugin,
+            ReplayPlugin,
+            PausePlugin,
+        ));
+    }
+}
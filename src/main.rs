@@ -1,20 +1,26 @@
+use std::fs;
+use std::path::PathBuf;
+
 use bevy::audio::VolumeLevel;
 use bevy::prelude::*;
 
-mod menu;
-use menu::MenuPlugin;
-
-mod game;
-use game::GamePlugin;
+use bevy_pong::arena::Arena;
+use bevy_pong::bindings::KeyBindings;
+use bevy_pong::controller::PaddleControllers;
+use bevy_pong::external::{BotAddress, ExternalBot};
+use bevy_pong::game::MatchSetup;
+use bevy_pong::paddle::Side;
+use bevy_pong::rules::MatchRules;
+use bevy_pong::{PongCorePlugin, WINDOW_HEIGHT, WINDOW_WIDTH};
 
-mod ball;
-use ball::BallPlugin;
+mod controls;
+use controls::{ControlsPlugin, CONTROLS_PATH};
 
-mod paddle;
-use paddle::PaddlePlugin;
+mod cursor;
+use cursor::CursorPlugin;
 
-mod score;
-use score::ScorePlugin;
+mod menu;
+use menu::{Arenas, MenuPlugin};
 
 mod ui;
 use ui::UiPlugin;
@@ -22,36 +28,38 @@ use ui::UiPlugin;
 mod end;
 use end::EndPlugin;
 
-// Consts to define the resolution of the game window in pixels
-pub const WINDOW_WIDTH: f32 = 802.;
-pub const WINDOW_HEIGHT: f32 = 455.;
-// Defines the pixel height of the top UI Scorebar
-pub const UI_HEIGHT: f32 = 47.;
+mod pause_menu;
+use pause_menu::PauseMenuPlugin;
+
+mod playback;
+use playback::PlaybackPlugin;
+
+mod sound;
+use sound::SoundPlugin;
+
+mod sprites;
+use sprites::SpritesPlugin;
+
 // Main font to be used
 pub const MAIN_FONT: &str = "fonts/Teko-Regular.ttf";
 
 // Set the global volume. The default volume is set to 1
 const VOLUME_LEVEL: f32 = 0.5;
-
-/// State management Enum to be used throughout the entire project.
-/// Each state corresponds to a different screen in the game as well
-/// as a different module in code.
-///
-/// - **Menu** refers to the Start up menu which displays the logo and
-/// prompts the player to select one or two players
-/// - **Game** refers to the actual game itself, and loads both the
-/// *game* module and it's respective components as well as the *ui* module
-/// - **End** refers to the simple end screen which loops back into the *Menu*
-/// state
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
-pub enum AppState {
-    #[default]
-    Menu,
-    Game,
-    End,
-}
+// Environment variable that can be used to replay a match from a known seed
+const SEED_VARIABLE: &str = "PONG_SEED";
+// Environment variables that hand a paddle over to an external bot
+const LEFT_BOT_VARIABLE: &str = "PONG_LEFT_BOT";
+const RIGHT_BOT_VARIABLE: &str = "PONG_RIGHT_BOT";
+// File that the rules of each match are read from
+const SETTINGS_PATH: &str = "settings.ron";
+// Folder that the arenas to pick from on the menu are read from
+const ARENAS_PATH: &str = "assets/arenas";
 
 fn main() {
+    let rules = load_rules();
+    let bindings = load_bindings();
+    let arenas = load_arenas();
+
     App::new()
         .add_plugins((
             // Set the resolution
@@ -64,18 +72,29 @@ fn main() {
                 }),
                 ..default()
             }),
-            // Plugins that are used during the actual game itself
+            // The rules of the game, which can also run without a window
+            PongCorePlugin,
+            // Presentation of the game itself
             // These plugins only run on the 'AppState::Game'
-            BallPlugin,
-            PaddlePlugin,
-            ScorePlugin,
+            SpritesPlugin,
+            SoundPlugin,
             UiPlugin,
+            PlaybackPlugin,
+            CursorPlugin,
             // Plugins which refer to state-management
             MenuPlugin,
-            GamePlugin,
             EndPlugin,
+            ControlsPlugin,
+            PauseMenuPlugin,
         ))
-        .add_state::<AppState>()
+        .insert_resource(rules)
+        .insert_resource(bindings)
+        .insert_resource(arenas)
+        .insert_resource(MatchSetup {
+            seed: seed_from_env(),
+            ..default()
+        })
+        .insert_resource(bots_from_env())
         // Do this outside of any state management
         .add_systems(Startup, init_game)
         .run();
@@ -86,3 +105,88 @@ fn init_game(mut commands: Commands, mut global_volume: ResMut<GlobalVolume>) {
     commands.spawn(Camera2dBundle::default());
     global_volume.volume = VolumeLevel::new(VOLUME_LEVEL);
 }
+
+// Read the seed for every match out of the environment, if one was given
+fn seed_from_env() -> Option<u64> {
+    let seed = std::env::var(SEED_VARIABLE).ok()?;
+    match seed.trim().parse() {
+        Ok(seed) => Some(seed),
+        Err(_) => {
+            eprintln!("Ignoring {SEED_VARIABLE}={seed}, as it is not a valid seed");
+            None
+        }
+    }
+}
+
+// Hand each side over to the bot named in the environment, if there is one
+fn bots_from_env() -> PaddleControllers {
+    let mut controllers = PaddleControllers::default();
+    for (side, variable) in [
+        (Side::Left, LEFT_BOT_VARIABLE),
+        (Side::Right, RIGHT_BOT_VARIABLE),
+    ] {
+        let Ok(address) = std::env::var(variable) else {
+            continue;
+        };
+        match address.parse::<BotAddress>() {
+            Ok(address) => controllers.set(side, move || ExternalBot::new(address.clone())),
+            Err(error) => eprintln!("Ignoring {variable}={address}, as {error}"),
+        }
+    }
+    controllers
+}
+
+// Read the match rules from the settings file, refusing to start if they are invalid
+fn load_rules() -> MatchRules {
+    match MatchRules::load(SETTINGS_PATH) {
+        Ok(rules) => rules,
+        Err(error) if error.is_not_found() => MatchRules::default(),
+        Err(error) => {
+            eprintln!("Invalid rules in {SETTINGS_PATH}: {error}");
+            std::process::exit(1);
+        }
+    }
+}
+
+// Read the players' keys from the controls file, falling back on the defaults
+fn load_bindings() -> KeyBindings {
+    match KeyBindings::load(CONTROLS_PATH) {
+        Ok(bindings) => bindings,
+        Err(error) if error.is_not_found() => KeyBindings::default(),
+        Err(error) => {
+            eprintln!("Ignoring the controls in {CONTROLS_PATH}, as {error}");
+            KeyBindings::default()
+        }
+    }
+}
+
+// Read every arena that can be picked on the menu, leaving out any that are
+// invalid. The classic board is always among them.
+fn load_arenas() -> Arenas {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(ARENAS_PATH) {
+        Ok(entries) => entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+            .collect(),
+        Err(error) => {
+            eprintln!("Could not read the arenas in {ARENAS_PATH}: {error}");
+            Vec::new()
+        }
+    };
+    paths.sort();
+
+    let mut arenas: Vec<Arena> = paths
+        .iter()
+        .filter_map(|path| match Arena::load(path) {
+            Ok(arena) => Some(arena),
+            Err(error) => {
+                eprintln!("Ignoring the arena in {}, as {error}", path.display());
+                None
+            }
+        })
+        .collect();
+    if !arenas.contains(&Arena::default()) {
+        arenas.insert(0, Arena::default());
+    }
+    Arenas(arenas)
+}
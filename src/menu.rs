@@ -1,41 +1,83 @@
 use bevy::prelude::*;
 
-use crate::{AppState, MAIN_FONT};
+use bevy_pong::arena::Arena;
+use bevy_pong::game::MatchSetup;
+use bevy_pong::gamepad::GamepadAssignments;
+use bevy_pong::paddle::Side;
+use bevy_pong::pointer::Pointer;
+use bevy_pong::replay::{Replay, ReplayPlayback};
+use bevy_pong::AppState;
+
+use crate::playback::LAST_REPLAY_PATH;
+use crate::MAIN_FONT;
 
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Menu), setup_start_menu)
+        app.init_resource::<Arenas>()
+            .add_systems(OnEnter(AppState::Menu), setup_start_menu)
             .add_systems(Update, button_system.run_if(in_state(AppState::Menu)))
+            .add_systems(
+                Update,
+                update_gamepad_text.run_if(
+                    in_state(AppState::Menu).and_then(resource_changed::<GamepadAssignments>()),
+                ),
+            )
             .add_systems(OnExit(AppState::Menu), cleanup_menu);
     }
 }
 
-// Holds assets to be despawned as well as passing
-// singleplayer status to the next state.
+// Holds assets to be despawned
 #[derive(Resource)]
-pub struct MenuData {
+struct MenuData {
     main_ui: Entity,
-    pub is_single_player: bool,
 }
 
 impl MenuData {
     fn new(main_ui: Entity) -> Self {
-        MenuData {
-            main_ui,
-            is_single_player: false,
-        }
+        MenuData { main_ui }
     }
 }
 
+/// The arenas that matches can be played in, in the order the menu cycles
+/// through them
+#[derive(Resource, Default)]
+pub struct Arenas(pub Vec<Arena>);
+
 #[derive(Component, PartialEq)]
 enum StartButton {
     SinglePlayer,
     Multiplayer,
+    Replay,
+    Controls,
+    Difficulty,
+    Arena,
+    Pointer(Side),
 }
 
-fn setup_start_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+// Shows the difficulty that single player matches will be played at
+#[derive(Component)]
+struct DifficultyText;
+
+// Shows the arena that the next match will be played in
+#[derive(Component)]
+struct ArenaText;
+
+// Shows what the player on a side steers their paddle with
+#[derive(Component)]
+struct PointerText(Side);
+
+// Shows which sides the gamepads have joined
+#[derive(Component)]
+struct GamepadText;
+
+fn setup_start_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    match_setup: Res<MatchSetup>,
+    gamepads: Res<GamepadAssignments>,
+) {
     let main_ui = commands
         .spawn(create_ui_base()) // Generate a UI template default
         .with_children(|parent| {
@@ -51,7 +93,7 @@ fn setup_start_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                 .spawn(NodeBundle {
                     style: Style {
                         flex_direction: FlexDirection::Row,
-                        width: Val::Percent(70.0),
+                        width: Val::Percent(90.0),
                         justify_content: JustifyContent::SpaceAround,
                         ..default()
                     },
@@ -82,15 +124,167 @@ fn setup_start_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                                 },
                             ));
                         });
+                    parent
+                        .spawn((create_button(), StartButton::Replay))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Replay",
+                                TextStyle {
+                                    font: asset_server.load(MAIN_FONT),
+                                    font_size: 40.,
+                                    color: Color::BLACK,
+                                },
+                            ));
+                        });
+                    parent
+                        .spawn((create_button(), StartButton::Controls))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Controls",
+                                TextStyle {
+                                    font: asset_server.load(MAIN_FONT),
+                                    font_size: 40.,
+                                    color: Color::BLACK,
+                                },
+                            ));
+                        });
                 });
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        width: Val::Percent(90.0),
+                        justify_content: JustifyContent::SpaceAround,
+                        margin: UiRect::top(Val::Px(20.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // Cycles through what the left player steers with
+                    parent
+                        .spawn((create_button(), StartButton::Pointer(Side::Left)))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    pointer_label(Side::Left, match_setup.left_pointer),
+                                    TextStyle {
+                                        font: asset_server.load(MAIN_FONT),
+                                        font_size: 32.,
+                                        color: Color::BLACK,
+                                    },
+                                ),
+                                PointerText(Side::Left),
+                            ));
+                        });
+                    // Cycles through the difficulties of the computer
+                    parent
+                        .spawn((create_button(), StartButton::Difficulty))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    match_setup.difficulty.to_string(),
+                                    TextStyle {
+                                        font: asset_server.load(MAIN_FONT),
+                                        font_size: 40.,
+                                        color: Color::BLACK,
+                                    },
+                                ),
+                                DifficultyText,
+                            ));
+                        });
+                    // Cycles through the arenas to play in
+                    parent
+                        .spawn((create_button(), StartButton::Arena))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    match_setup.arena.name.clone(),
+                                    TextStyle {
+                                        font: asset_server.load(MAIN_FONT),
+                                        font_size: 32.,
+                                        color: Color::BLACK,
+                                    },
+                                ),
+                                ArenaText,
+                            ));
+                        });
+                    // Cycles through what the right player steers with
+                    parent
+                        .spawn((create_button(), StartButton::Pointer(Side::Right)))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    pointer_label(Side::Right, match_setup.right_pointer),
+                                    TextStyle {
+                                        font: asset_server.load(MAIN_FONT),
+                                        font_size: 32.,
+                                        color: Color::BLACK,
+                                    },
+                                ),
+                                PointerText(Side::Right),
+                            ));
+                        });
+                });
+            parent.spawn((
+                TextBundle::from_section(
+                    gamepad_status(&gamepads),
+                    TextStyle {
+                        font: asset_server.load(MAIN_FONT),
+                        font_size: 24.,
+                        color: Color::GRAY,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(20.0)),
+                    ..default()
+                }),
+                GamepadText,
+            ));
         })
         // Make sure commands returns the Entity so that we can pass it into the resource
         .id();
     commands.insert_resource(MenuData::new(main_ui));
 }
 
+fn pointer_label(side: Side, pointer: Option<Pointer>) -> String {
+    let side = match side {
+        Side::Left => "Left",
+        Side::Right => "Right",
+    };
+    match pointer {
+        Some(pointer) => format!("{side}: {pointer}"),
+        None => format!("{side}: Keys"),
+    }
+}
+
+fn gamepad_status(gamepads: &GamepadAssignments) -> String {
+    if gamepads.is_empty() {
+        return "Press a button on a gamepad to join".to_string();
+    }
+    let joined = |gamepad: Option<Gamepad>| match gamepad {
+        Some(_) => "Gamepad",
+        None => "Keyboard",
+    };
+    format!(
+        "Left: {}    Right: {}",
+        joined(gamepads.left),
+        joined(gamepads.right)
+    )
+}
+
+fn update_gamepad_text(
+    gamepads: Res<GamepadAssignments>,
+    mut text_query: Query<&mut Text, With<GamepadText>>,
+) {
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = gamepad_status(&gamepads);
+    }
+}
+
 fn cleanup_menu(mut commands: Commands, menu_data: Res<MenuData>) {
     commands.entity(menu_data.main_ui).despawn_recursive();
+    commands.remove_resource::<MenuData>();
 }
 
 pub fn create_button() -> ButtonBundle {
@@ -125,6 +319,7 @@ pub fn create_ui_base() -> NodeBundle {
     }
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn button_system(
     mut interaction_query: Query<
         (
@@ -136,14 +331,71 @@ fn button_system(
         Changed<Interaction>,
     >,
     mut next_state: ResMut<NextState<AppState>>,
-    mut menu_data: ResMut<MenuData>,
+    mut difficulty_text_query: Query<&mut Text, (With<DifficultyText>, Without<PointerText>)>,
+    mut pointer_text_query: Query<(&mut Text, &PointerText)>,
+    mut arena_text_query: Query<
+        &mut Text,
+        (
+            With<ArenaText>,
+            Without<DifficultyText>,
+            Without<PointerText>,
+        ),
+    >,
+    arenas: Res<Arenas>,
+    mut match_setup: ResMut<MatchSetup>,
+    mut commands: Commands,
 ) {
     for (interaction, mut color, mut border_color, start_button) in interaction_query.iter_mut() {
         match *interaction {
-            Interaction::Pressed => {
-                menu_data.is_single_player = *start_button == StartButton::SinglePlayer;
-                next_state.set(AppState::Game);
-            }
+            Interaction::Pressed => match start_button {
+                StartButton::SinglePlayer | StartButton::Multiplayer => {
+                    match_setup.is_single_player = *start_button == StartButton::SinglePlayer;
+                    next_state.set(AppState::Game);
+                }
+                // Watch the most recently played match
+                StartButton::Replay => match Replay::load(LAST_REPLAY_PATH) {
+                    Ok(replay) => {
+                        commands.insert_resource(ReplayPlayback::new(replay));
+                        next_state.set(AppState::Replay);
+                    }
+                    Err(error) => warn!("Could not play {LAST_REPLAY_PATH}: {error}"),
+                },
+                StartButton::Controls => next_state.set(AppState::Controls),
+                StartButton::Difficulty => {
+                    match_setup.difficulty = match_setup.difficulty.next();
+                    for mut text in difficulty_text_query.iter_mut() {
+                        text.sections[0].value = match_setup.difficulty.to_string();
+                    }
+                }
+                StartButton::Arena => {
+                    // Carry on from wherever the current arena is in the list
+                    let next = arenas
+                        .0
+                        .iter()
+                        .position(|arena| *arena == match_setup.arena)
+                        .map_or(0, |index| (index + 1) % arenas.0.len());
+                    if let Some(arena) = arenas.0.get(next) {
+                        match_setup.arena = arena.clone();
+                    }
+                    for mut text in arena_text_query.iter_mut() {
+                        text.sections[0].value = match_setup.arena.name.clone();
+                    }
+                }
+                StartButton::Pointer(side) => {
+                    let pointer = match side {
+                        Side::Left => &mut match_setup.left_pointer,
+                        Side::Right => &mut match_setup.right_pointer,
+                    };
+                    *pointer = Pointer::next(*pointer);
+                    let label = pointer_label(*side, *pointer);
+                    for (mut text, _) in pointer_text_query
+                        .iter_mut()
+                        .filter(|(_, text_side)| text_side.0 == *side)
+                    {
+                        text.sections[0].value = label.clone();
+                    }
+                }
+            },
             Interaction::Hovered => {
                 *color = Color::rgb_u8(96, 36, 78).into();
                 *border_color = Color::INDIGO.into();
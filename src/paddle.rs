@@ -1,77 +1,248 @@
-use crate::{AppState, UI_HEIGHT, WINDOW_HEIGHT};
+use crate::ai::{self, ComputerMind, ComputerSkill};
+use crate::arena::Lane;
+use crate::controller::{BallState, Controller, GameSnapshot};
+use crate::game::MatchClock;
+use crate::gamepad::GamepadInput;
+use crate::physics::{PhysicsSet, Position};
+use crate::pointer::{self, Pointer, Pointers};
+use crate::{match_in_progress, AppState};
 
 use super::ball::Ball;
+use super::rules::MatchRules;
+use super::score::Score;
 use bevy::prelude::*;
 
-const PADDLE_SPEED: f32 = 500.;
-pub const PADDLE_HEIGHT: f32 = 120.;
 pub const PADDLE_WIDTH: f32 = 17.;
-const AI_SPEED_MODIFIER: f32 = 0.8;
 
 pub struct PaddlePlugin;
 
 impl Plugin for PaddlePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, paddle_control.run_if(in_state(AppState::Game)));
+        // Keyboard input is not available without a window, so make sure
+        // the resource exists for headless matches
+        app.init_resource::<Input<KeyCode>>()
+            .add_systems(
+                FixedUpdate,
+                // Nobody is asked how to move while the match is paused, so
+                // that the computer's reactions and rolls don't carry on
+                // without it
+                read_paddle_input
+                    .in_set(PhysicsSet::Input)
+                    .run_if(in_state(AppState::Game).and_then(match_in_progress)),
+            )
+            .add_systems(
+                FixedUpdate,
+                paddle_control
+                    .in_set(PhysicsSet::Paddles)
+                    .run_if(match_in_progress),
+            );
+    }
+}
+
+/// Which end of the board a paddle defends
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    /// The side across the board
+    pub fn opponent(self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
     }
 }
 
 #[derive(Component)]
 pub enum Paddle {
-    Computer,
+    Computer {
+        skill: ComputerSkill,
+    },
     Player {
         input_up: KeyCode,
         input_down: KeyCode,
+        input_serve: KeyCode,
+        /// A pointer that the paddle follows as well as the keys
+        pointer: Option<Pointer>,
     },
+    /// Moved by the `Controller` registered for its side
+    Controlled,
 }
 
-fn paddle_control(
-    mut paddle_query: Query<(&mut Transform, &Paddle), Without<Ball>>,
-    ball_query: Query<&Transform, With<Ball>>,
+/// How a paddle is being asked to move on the current step, from -1 for
+/// full speed downwards to 1 for full speed upwards. Filled in from the
+/// keyboard, a gamepad, a pointer, the computer or a controller during a
+/// match, and from the recording during a replay.
+#[derive(Component, Default, Clone, Copy, PartialEq, Debug)]
+pub struct PaddleInput(pub f32);
+
+/// Whether a paddle is asking to serve the ball on the current step. Only
+/// players ever ask, as everyone else serves as soon as they are allowed to.
+#[derive(Component, Default, Clone, Copy, PartialEq, Debug)]
+pub struct ServeInput(pub bool);
+
+/// How tall a paddle is, which is the height set by the rules unless a
+/// power-up has grown or shrunk it
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct PaddleHeight(pub f32);
+
+/// How fast a paddle moved on the last step, in pixels per second upwards
+#[derive(Component, Default, Clone, Copy, PartialEq, Debug)]
+pub struct PaddleVelocity(pub f32);
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn read_paddle_input(
+    mut paddle_query: Query<
+        (
+            &mut PaddleInput,
+            &mut ServeInput,
+            &Position,
+            &Side,
+            &Paddle,
+            Option<&mut ComputerMind>,
+            Option<&mut Controller>,
+        ),
+        Without<Ball>,
+    >,
+    opponent_query: Query<(&Position, &Side), With<Paddle>>,
+    ball_query: Query<(&Position, &Ball)>,
+    score_query: Query<&Score>,
     keyboard_input: Res<Input<KeyCode>>,
-    time: Res<Time>,
+    gamepad_input: GamepadInput,
+    pointers: Res<Pointers>,
+    clock: Res<MatchClock>,
+    fixed_time: Res<FixedTime>,
+    rules: Res<MatchRules>,
 ) {
-    for (mut transform, controller) in paddle_query.iter_mut() {
-        match *controller {
+    for (mut input, mut serve_input, position, side, paddle, mind, controller) in
+        paddle_query.iter_mut()
+    {
+        // Only the computer and other controllers need to see the match
+        let snapshot = || {
+            // With more than one ball about, the one to worry about is shown
+            let balls = ball_query.iter().map(|(ball_position, ball)| BallState {
+                position: ball_position.current,
+                velocity: ball.velocity(),
+            });
+            let ball = ai::most_threatening(balls, position.current.x)
+                .expect("There should always be a ball during a match");
+            GameSnapshot {
+                side: *side,
+                paddle: position.current,
+                opponent: opponent_query
+                    .iter()
+                    .find(|(_, opponent_side)| *opponent_side != side)
+                    .map(|(opponent_position, _)| opponent_position.current),
+                ball,
+                score: *score_query
+                    .get_single()
+                    .expect("Only one score object should exist at a time!"),
+                elapsed: clock.elapsed,
+                rules: &rules,
+                delta_seconds: fixed_time.period.as_secs_f32(),
+            }
+        };
+
+        serve_input.0 = match paddle {
+            Paddle::Player { input_serve, .. } => {
+                keyboard_input.pressed(*input_serve) || gamepad_input.serve(*side)
+            }
+            Paddle::Computer { .. } | Paddle::Controlled => false,
+        };
+
+        input.0 = match paddle {
             Paddle::Player {
                 input_up,
                 input_down,
+                pointer,
+                ..
             } => {
                 // Move the paddle based on user input
-                if keyboard_input.pressed(input_up) {
-                    move_paddle(&mut transform, 1.0, &time);
+                let mut direction = 0.0;
+                if keyboard_input.pressed(*input_up) {
+                    direction += 1.0;
                 }
-                if keyboard_input.pressed(input_down) {
-                    move_paddle(&mut transform, -1.0, &time);
+                if keyboard_input.pressed(*input_down) {
+                    direction -= 1.0;
                 }
-            }
-            Paddle::Computer => {
-                // Extract the ball query
-                let ball_y_pos = ball_query
-                    .get_single()
-                    .expect("Only one ball has been implemented yet!")
-                    .translation
-                    .y;
-
-                // Move the paddle towards the ball
-                if ball_y_pos > transform.translation.y {
-                    move_paddle(&mut transform, AI_SPEED_MODIFIER, &time);
-                } else {
-                    move_paddle(&mut transform, -AI_SPEED_MODIFIER, &time);
+                if let Some(target) = pointer.and_then(|pointer| pointers.target(pointer, *side)) {
+                    let step_distance = rules.paddle_speed * fixed_time.period.as_secs_f32();
+                    direction += pointer::follow(target, position.current.y, step_distance);
                 }
+                // A gamepad that has joined on this side plays alongside the keys
+                (direction + gamepad_input.movement(*side)).clamp(-1.0, 1.0)
             }
-        }
+            Paddle::Computer { skill } => mind
+                .expect("Computer paddles should have a mind of their own")
+                .input(skill, &snapshot()),
+            Paddle::Controlled => controller
+                .expect("Controlled paddles should have a controller")
+                .0
+                .movement(&snapshot()),
+        };
+    }
+}
+
+fn paddle_control(
+    mut paddle_query: Query<(
+        &mut Position,
+        &mut PaddleVelocity,
+        &PaddleHeight,
+        &PaddleInput,
+        &Lane,
+    )>,
+    fixed_time: Res<FixedTime>,
+    rules: Res<MatchRules>,
+) {
+    let delta_seconds = fixed_time.period.as_secs_f32();
+
+    for (mut position, mut velocity, height, input, lane) in paddle_query.iter_mut() {
+        move_paddle(
+            &mut position,
+            &mut velocity,
+            height.0,
+            lane,
+            input.0.clamp(-1.0, 1.0),
+            delta_seconds,
+            &rules,
+        );
     }
 }
 
-fn move_paddle(transform: &mut Transform, multiplier: f32, time: &Time) {
-    let half_paddle_height = PADDLE_HEIGHT / 2.0;
-    let vertical_border = WINDOW_HEIGHT / 2.0;
-    let y_min = -vertical_border + half_paddle_height;
-    // Make sure paddle doesn't clash with UI
-    let y_max = vertical_border - UI_HEIGHT - half_paddle_height;
+fn move_paddle(
+    position: &mut Position,
+    velocity: &mut PaddleVelocity,
+    height: f32,
+    lane: &Lane,
+    multiplier: f32,
+    delta_seconds: f32,
+    rules: &MatchRules,
+) {
+    let target_speed = rules.paddle_speed * multiplier;
+    let speed = match &rules.paddle_momentum {
+        // Head for the speed asked for, or come to a stop when let go of
+        Some(momentum) => {
+            let change = if multiplier == 0.0 {
+                momentum.friction
+            } else {
+                momentum.acceleration
+            } * delta_seconds;
+            velocity.0 + (target_speed - velocity.0).clamp(-change, change)
+        }
+        None => target_speed,
+    };
 
-    let y_pos = &mut transform.translation.y;
+    let y_pos = &mut position.current.y;
+    let previous_y = *y_pos;
+    *y_pos = lane.clamp(*y_pos + speed * delta_seconds, height);
 
-    *y_pos = (*y_pos + PADDLE_SPEED * multiplier * time.delta_seconds()).clamp(y_min, y_max);
+    // Running into the end of its lane stops the paddle dead
+    velocity.0 = if delta_seconds > 0.0 {
+        (*y_pos - previous_y) / delta_seconds
+    } else {
+        0.0
+    };
 }
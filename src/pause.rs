@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+use crate::bindings::KeyBindings;
+use crate::gamepad::GamepadInput;
+use crate::AppState;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<PauseState>()
+            .add_event::<RestartMatch>()
+            .add_systems(Update, toggle_pause.run_if(in_state(AppState::Game)))
+            .add_systems(Update, restart_game.run_if(on_event::<RestartMatch>()))
+            .add_systems(OnExit(AppState::Game), unpause);
+    }
+}
+
+/// Whether the match being played is paused. Nothing in the match moves,
+/// and its clock stands still, while it is `Paused`.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+/// Send to throw away the match being played and start a new one with the
+/// same setup
+#[derive(Event)]
+pub struct RestartMatch;
+
+// Either player's pause key pauses the game, and resumes it once paused, as
+// does Start on a gamepad that has joined
+fn toggle_pause(
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    gamepad_input: GamepadInput,
+    pause_state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if !keyboard_input.any_just_pressed(bindings.pause_keys())
+        && !gamepad_input.just_pressed(GamepadButtonType::Start)
+    {
+        return;
+    }
+    next_state.set(match pause_state.get() {
+        PauseState::Running => PauseState::Paused,
+        PauseState::Paused => PauseState::Running,
+    });
+}
+
+// A match is never left paused once it is over
+fn unpause(mut next_state: ResMut<NextState<PauseState>>) {
+    next_state.set(PauseState::Running);
+}
+
+// Leave and enter the game again in place, so that everything attached to
+// the match (including its screens and its recording) starts over
+fn restart_game(world: &mut World) {
+    if *world.resource::<State<AppState>>().get() != AppState::Game {
+        return;
+    }
+    world.run_schedule(OnExit(AppState::Game));
+    world.run_schedule(OnEnter(AppState::Game));
+}
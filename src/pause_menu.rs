@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+
+use bevy_pong::pause::{PauseState, RestartMatch};
+use bevy_pong::AppState;
+
+use crate::{menu, MAIN_FONT};
+
+pub struct PauseMenuPlugin;
+
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(PauseState::Paused), setup_pause_menu)
+            .add_systems(Update, pause_button.run_if(in_state(PauseState::Paused)))
+            .add_systems(OnExit(PauseState::Paused), cleanup_pause_menu);
+    }
+}
+
+// Holds the overlay to be despawned
+#[derive(Resource)]
+struct PauseMenuData(Entity);
+
+#[derive(Component)]
+enum PauseButton {
+    Resume,
+    Restart,
+    Quit,
+}
+
+fn setup_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let mut ui_base = menu::create_ui_base();
+    // Let the frozen match show through, and sit on top of the scorebar
+    ui_base.style.height = Val::Percent(100.0);
+    ui_base.style.position_type = PositionType::Absolute;
+    ui_base.style.row_gap = Val::Px(15.0);
+    ui_base.background_color = Color::rgba(0.0, 0.0, 0.0, 0.7).into();
+    ui_base.z_index = ZIndex::Global(10);
+
+    let pause_ui = commands
+        .spawn(ui_base)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Paused",
+                TextStyle {
+                    font: asset_server.load(MAIN_FONT),
+                    font_size: 120.0,
+                    color: Color::WHITE,
+                },
+            ));
+            for (button, label) in [
+                (PauseButton::Resume, "Resume"),
+                (PauseButton::Restart, "Restart"),
+                (PauseButton::Quit, "Quit"),
+            ] {
+                parent
+                    .spawn((menu::create_button(), button))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            label,
+                            TextStyle {
+                                font: asset_server.load(MAIN_FONT),
+                                font_size: 40.,
+                                color: Color::BLACK,
+                            },
+                        ));
+                    });
+            }
+        })
+        .id();
+    commands.insert_resource(PauseMenuData(pause_ui));
+}
+
+fn cleanup_pause_menu(mut commands: Commands, pause_menu_data: Res<PauseMenuData>) {
+    commands.entity(pause_menu_data.0).despawn_recursive();
+    commands.remove_resource::<PauseMenuData>();
+}
+
+fn pause_button(
+    mut interaction_query: Query<
+        (
+            &Interaction,
+            &mut BackgroundColor,
+            &mut BorderColor,
+            &PauseButton,
+        ),
+        Changed<Interaction>,
+    >,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut restart_events: EventWriter<RestartMatch>,
+) {
+    for (interaction, mut color, mut border_color, pause_button) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                match pause_button {
+                    PauseButton::Resume => {}
+                    PauseButton::Restart => restart_events.send(RestartMatch),
+                    // Leaving the game cleans up the match and its scorebar
+                    PauseButton::Quit => next_state.set(AppState::Menu),
+                }
+                next_pause_state.set(PauseState::Running);
+            }
+            Interaction::Hovered => {
+                *color = Color::rgb_u8(96, 36, 78).into();
+                *border_color = Color::INDIGO.into();
+            }
+            Interaction::None => {
+                *color = Color::rgb_u8(153, 48, 122).into();
+                *border_color = Color::PURPLE.into();
+            }
+        }
+    }
+}
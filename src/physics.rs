@@ -0,0 +1,466 @@
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+
+// Length of a single simulation step in seconds. The ball and paddles are
+// only ever moved in multiples of this, regardless of the frame rate.
+pub const PHYSICS_TIMESTEP: f32 = 1.0 / 120.0;
+
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FixedTime::new_from_secs(PHYSICS_TIMESTEP))
+            .configure_sets(
+                FixedUpdate,
+                (
+                    PhysicsSet::Prepare,
+                    PhysicsSet::Input,
+                    PhysicsSet::Paddles,
+                    PhysicsSet::Ball,
+                    PhysicsSet::PowerUps,
+                    PhysicsSet::Score,
+                    PhysicsSet::Victory,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                store_previous_positions.in_set(PhysicsSet::Prepare),
+            )
+            .add_systems(
+                PostUpdate,
+                interpolate_transforms.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Ordering of the systems that run inside of the `FixedUpdate` schedule.
+/// Every simulation system must belong to one of these sets so that a step
+/// always runs in the same order, which keeps matches reproducible.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PhysicsSet {
+    /// Remembers where everything was before the step begins
+    Prepare,
+    /// Decides how each paddle wants to move
+    Input,
+    /// Moves the paddles from their inputs
+    Paddles,
+    /// Moves the ball and resolves its collisions
+    Ball,
+    /// Hands out the power-ups that the ball has passed through, and runs
+    /// down the ones in effect
+    PowerUps,
+    /// Applies any points scored during the step
+    Score,
+    /// Decides whether a set or the match has been won
+    Victory,
+}
+
+/// The simulated position of an entity. Gameplay systems read and write
+/// `current`, while the `Transform` is only ever derived from it by
+/// interpolating between the last two simulation steps.
+#[derive(Component, Default, Clone, Copy)]
+pub struct Position {
+    pub current: Vec2,
+    pub previous: Vec2,
+}
+
+impl Position {
+    pub fn new(position: Vec2) -> Self {
+        Position {
+            current: position,
+            previous: position,
+        }
+    }
+
+    /// Moves the entity without interpolating through the space in between
+    pub fn teleport(&mut self, position: Vec2) {
+        self.current = position;
+        self.previous = position;
+    }
+}
+
+fn store_previous_positions(mut position_query: Query<&mut Position>) {
+    for mut position in position_query.iter_mut() {
+        position.previous = position.current;
+    }
+}
+
+// Blend between the last two steps by how far we are into the next one
+fn interpolate_transforms(
+    fixed_time: Res<FixedTime>,
+    mut position_query: Query<(&mut Transform, &Position)>,
+) {
+    let alpha =
+        (fixed_time.accumulated().as_secs_f32() / fixed_time.period.as_secs_f32()).clamp(0.0, 1.0);
+
+    for (mut transform, position) in position_query.iter_mut() {
+        let interpolated = position.previous.lerp(position.current, alpha);
+        transform.translation.x = interpolated.x;
+        transform.translation.y = interpolated.y;
+    }
+}
+
+/// Where a swept movement first touched something
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepHit {
+    /// Fraction of the movement, from 0 to 1, that was completed before impact
+    pub time: f32,
+    /// Surface normal of the side that was hit
+    pub normal: Vec2,
+}
+
+/// Casts a point from `origin` along `motion` against an axis aligned box,
+/// returning the earliest point of entry within the movement. To sweep a box
+/// rather than a point, grow `half_size` by the half size of the moving box.
+///
+/// Movements which begin inside of the box are not reported as a hit.
+pub fn sweep_aabb(origin: Vec2, motion: Vec2, center: Vec2, half_size: Vec2) -> Option<SweepHit> {
+    let min = center - half_size;
+    let max = center + half_size;
+
+    let mut entry = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+    let mut normal = Vec2::ZERO;
+
+    for axis in 0..2 {
+        if motion[axis] == 0.0 {
+            // Moving parallel to this slab, so we have to already be within it
+            if origin[axis] <= min[axis] || origin[axis] >= max[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let near_time = (min[axis] - origin[axis]) / motion[axis];
+        let far_time = (max[axis] - origin[axis]) / motion[axis];
+        let (axis_entry, axis_exit) = if near_time < far_time {
+            (near_time, far_time)
+        } else {
+            (far_time, near_time)
+        };
+
+        if axis_entry > entry {
+            entry = axis_entry;
+            normal = Vec2::ZERO;
+            normal[axis] = -motion[axis].signum();
+        }
+        exit = exit.min(axis_exit);
+    }
+
+    if entry > exit || !(0.0..=1.0).contains(&entry) {
+        return None;
+    }
+
+    Some(SweepHit {
+        time: entry,
+        normal,
+    })
+}
+
+/// Casts a circle of `radius` from `origin` along `motion` against the line
+/// segment from `start` to `end`, which may lie at any angle. The segment is
+/// grown by the radius into a capsule, so that the circle can be swept as a
+/// point and glance off of the rounded ends as well as the flat sides.
+///
+/// Like `sweep_aabb`, movements which begin inside of the capsule or which
+/// are heading away from it are not reported as a hit.
+pub fn sweep_segment(
+    origin: Vec2,
+    motion: Vec2,
+    start: Vec2,
+    end: Vec2,
+    radius: f32,
+) -> Option<SweepHit> {
+    let direction = end - start;
+    let length_squared = direction.length_squared();
+    let mut earliest: Option<SweepHit> = None;
+
+    // The two flat sides, each pushed out from the segment by the radius
+    if length_squared > 0.0 {
+        let side_normal = direction.perp().normalize();
+        for normal in [side_normal, -side_normal] {
+            let approach = motion.dot(normal);
+            let distance = (origin - start).dot(normal);
+            if approach >= 0.0 || distance < radius - SWEEP_TOLERANCE {
+                continue;
+            }
+
+            let time = ((distance - radius) / -approach).max(0.0);
+            if time > 1.0 {
+                continue;
+            }
+            let along = (origin + motion * time - start).dot(direction) / length_squared;
+            if (0.0..=1.0).contains(&along) {
+                earliest = Some(SweepHit { time, normal });
+            }
+        }
+    }
+
+    // And the rounded ends
+    for point in [start, end] {
+        if let Some(hit) = sweep_circle(origin, motion, point, radius) {
+            if earliest.is_none_or(|closest| hit.time < closest.time) {
+                earliest = Some(hit);
+            }
+        }
+    }
+
+    earliest
+}
+
+// How far inside of a surface a movement may start and still be swept
+// against it, which soaks up the rounding left over from the last bounce
+const SWEEP_TOLERANCE: f32 = 0.01;
+
+// Casts a point against a circle around `center`
+fn sweep_circle(origin: Vec2, motion: Vec2, center: Vec2, radius: f32) -> Option<SweepHit> {
+    let offset = origin - center;
+    let a = motion.length_squared();
+    let b = 2.0 * offset.dot(motion);
+    let c = offset.length_squared() - radius * radius;
+    // Starting inside, standing still or moving away
+    if c < 0.0 || a == 0.0 || b >= 0.0 {
+        return None;
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let time = (-b - discriminant.sqrt()) / (2.0 * a);
+    if !(0.0..=1.0).contains(&time) {
+        return None;
+    }
+
+    Some(SweepHit {
+        time,
+        normal: (offset + motion * time).normalize(),
+    })
+}
+
+/// Returns the shortest push that moves a circle of `radius` at `center` off
+/// of the segment from `start` to `end`, or `None` if they are not touching
+pub fn segment_penetration(center: Vec2, start: Vec2, end: Vec2, radius: f32) -> Option<Vec2> {
+    let closest = closest_point_on_segment(center, start, end);
+    let offset = center - closest;
+    let distance = offset.length();
+    if distance >= radius {
+        return None;
+    }
+
+    // A circle centered right on the segment is pushed out to one side of it
+    let normal = if distance > 0.0 {
+        offset / distance
+    } else {
+        (end - start).perp().normalize_or_zero()
+    };
+    Some(normal * (radius - distance))
+}
+
+/// The point along the segment from `start` to `end` that is nearest to `point`
+pub fn closest_point_on_segment(point: Vec2, start: Vec2, end: Vec2) -> Vec2 {
+    let direction = end - start;
+    let length_squared = direction.length_squared();
+    if length_squared == 0.0 {
+        return start;
+    }
+    let along = ((point - start).dot(direction) / length_squared).clamp(0.0, 1.0);
+    start + direction * along
+}
+
+/// Returns the shortest push that moves box `a` out of box `b`, or `None` if
+/// the two boxes are not overlapping
+pub fn penetration(
+    a_center: Vec2,
+    a_half_size: Vec2,
+    b_center: Vec2,
+    b_half_size: Vec2,
+) -> Option<Vec2> {
+    let offset = a_center - b_center;
+    let overlap = a_half_size + b_half_size - offset.abs();
+
+    if overlap.x <= 0.0 || overlap.y <= 0.0 {
+        return None;
+    }
+
+    // Push out along whichever axis has the least overlap
+    if overlap.x < overlap.y {
+        Some(Vec2::new(overlap.x * sign_or_one(offset.x), 0.0))
+    } else {
+        Some(Vec2::new(0.0, overlap.y * sign_or_one(offset.y)))
+    }
+}
+
+// Like `signum`, but treats zero as positive so that centered boxes still separate
+fn sign_or_one(value: f32) -> f32 {
+    if value < 0.0 {
+        -1.0
+    } else {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ball::BALL_SIZE;
+    use crate::paddle::PADDLE_WIDTH;
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-4, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn boxes_are_hit_at_the_face_they_are_reached_through() {
+        let hit = sweep_aabb(
+            Vec2::new(-100.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::ZERO,
+            Vec2::splat(10.0),
+        )
+        .unwrap();
+        assert_near(hit.time, 0.9);
+        assert_eq!(hit.normal, Vec2::NEG_X);
+
+        let hit = sweep_aabb(
+            Vec2::new(5.0, 50.0),
+            Vec2::new(0.0, -80.0),
+            Vec2::ZERO,
+            Vec2::splat(10.0),
+        )
+        .unwrap();
+        assert_near(hit.time, 0.5);
+        assert_eq!(hit.normal, Vec2::Y);
+    }
+
+    #[test]
+    fn grazing_past_a_box_misses_it() {
+        // Sliding right along the top face
+        let along_face = sweep_aabb(
+            Vec2::new(-100.0, 10.0),
+            Vec2::new(200.0, 0.0),
+            Vec2::ZERO,
+            Vec2::splat(10.0),
+        );
+        assert_eq!(along_face, None);
+        // Dropping past the corner without reaching it
+        let past_corner = sweep_aabb(
+            Vec2::new(-100.0, 20.0),
+            Vec2::new(100.0, -5.0),
+            Vec2::ZERO,
+            Vec2::splat(10.0),
+        );
+        assert_eq!(past_corner, None);
+        // Stopping short
+        let short = sweep_aabb(
+            Vec2::new(-100.0, 0.0),
+            Vec2::new(50.0, 0.0),
+            Vec2::ZERO,
+            Vec2::splat(10.0),
+        );
+        assert_eq!(short, None);
+    }
+
+    #[test]
+    fn movements_starting_inside_a_box_are_left_to_penetration() {
+        let inside = sweep_aabb(
+            Vec2::new(2.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::ZERO,
+            Vec2::splat(10.0),
+        );
+        assert_eq!(inside, None);
+
+        let push = penetration(
+            Vec2::new(2.0, 0.0),
+            Vec2::splat(5.0),
+            Vec2::ZERO,
+            Vec2::splat(10.0),
+        );
+        assert_eq!(push, Some(Vec2::new(13.0, 0.0)));
+        // Centered boxes still separate
+        let push = penetration(
+            Vec2::ZERO,
+            Vec2::new(5.0, 20.0),
+            Vec2::ZERO,
+            Vec2::splat(10.0),
+        );
+        assert_eq!(push, Some(Vec2::new(15.0, 0.0)));
+        let apart = penetration(
+            Vec2::new(15.0, 0.0),
+            Vec2::splat(5.0),
+            Vec2::ZERO,
+            Vec2::splat(10.0),
+        );
+        assert_eq!(apart, None);
+    }
+
+    #[test]
+    fn a_ball_crossing_a_paddle_within_one_step_still_hits_it() {
+        // Far enough in a single step to start in front of the paddle and end
+        // up clean out the other side of it
+        let motion = Vec2::new(10_000.0 * PHYSICS_TIMESTEP, 0.0);
+        let half_size = Vec2::new(PADDLE_WIDTH, 120.0) / 2.0 + Vec2::splat(BALL_SIZE / 2.0);
+        let origin = Vec2::new(-half_size.x - 10.0, 0.0);
+        assert!(origin.x + motion.x > half_size.x);
+
+        let hit = sweep_aabb(origin, motion, Vec2::ZERO, half_size).unwrap();
+        assert_near(hit.time, 10.0 / motion.x);
+        assert_eq!(hit.normal, Vec2::NEG_X);
+    }
+
+    #[test]
+    fn segments_are_hit_on_their_sides_and_ends() {
+        let (start, end) = (Vec2::new(-50.0, 0.0), Vec2::new(50.0, 0.0));
+
+        let side = sweep_segment(
+            Vec2::new(0.0, 40.0),
+            Vec2::new(0.0, -60.0),
+            start,
+            end,
+            10.0,
+        )
+        .unwrap();
+        assert_near(side.time, 0.5);
+        assert_near(side.normal.y, 1.0);
+
+        let cap = sweep_segment(
+            Vec2::new(100.0, 0.0),
+            Vec2::new(-80.0, 0.0),
+            start,
+            end,
+            10.0,
+        )
+        .unwrap();
+        assert_near(cap.time, 0.5);
+        assert_near(cap.normal.x, 1.0);
+    }
+
+    #[test]
+    fn segments_are_missed_when_grazed_or_started_on() {
+        let (start, end) = (Vec2::new(-50.0, 0.0), Vec2::new(50.0, 0.0));
+
+        // Passing just beyond the rounded end
+        let grazing = sweep_segment(
+            Vec2::new(61.0, 40.0),
+            Vec2::new(0.0, -80.0),
+            start,
+            end,
+            10.0,
+        );
+        assert_eq!(grazing, None);
+        // Already touching, which is left to `segment_penetration`
+        let inside = sweep_segment(Vec2::new(0.0, 5.0), Vec2::new(0.0, -20.0), start, end, 10.0);
+        assert_eq!(inside, None);
+
+        let push = segment_penetration(Vec2::new(0.0, 5.0), start, end, 10.0).unwrap();
+        assert_near(push.y, 5.0);
+        let centered = segment_penetration(Vec2::ZERO, start, end, 10.0).unwrap();
+        assert_near(centered.length(), 10.0);
+        assert_eq!(
+            segment_penetration(Vec2::new(0.0, 12.0), start, end, 10.0),
+            None
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+use bevy_pong::physics::PHYSICS_TIMESTEP;
+use bevy_pong::replay::{LastReplay, ReplayPlayback};
+use bevy_pong::AppState;
+
+use crate::MAIN_FONT;
+
+// Where the replay of the most recent match is kept
+pub const LAST_REPLAY_PATH: &str = "replays/last.ron";
+// How far the arrow keys jump through a replay
+const SEEK_SECONDS: f32 = 5.0;
+const MIN_PLAYBACK_SPEED: f32 = 0.25;
+const MAX_PLAYBACK_SPEED: f32 = 4.0;
+
+pub struct PlaybackPlugin;
+
+impl Plugin for PlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            save_last_replay.run_if(resource_added::<LastReplay>()),
+        )
+        .add_systems(OnEnter(AppState::Replay), setup_playback_ui)
+        .add_systems(
+            Update,
+            (playback_controls, update_playback_text).run_if(in_state(AppState::Replay)),
+        )
+        .add_systems(OnExit(AppState::Replay), cleanup_playback);
+    }
+}
+
+#[derive(Component)]
+struct PlaybackText;
+
+// Despawn the overlay when leaving the replay
+#[derive(Resource)]
+struct PlaybackData(Entity);
+
+fn save_last_replay(last_replay: Res<LastReplay>) {
+    match last_replay.0.save(LAST_REPLAY_PATH) {
+        Ok(()) => info!("Saved the replay of the match to {LAST_REPLAY_PATH}"),
+        Err(error) => warn!("Could not save the replay of the match: {error}"),
+    }
+}
+
+fn setup_playback_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let overlay = commands
+        .spawn((
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load(MAIN_FONT),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(5.0),
+                left: Val::Px(10.0),
+                ..default()
+            }),
+            PlaybackText,
+        ))
+        .id();
+    commands.insert_resource(PlaybackData(overlay));
+}
+
+// Space pauses, up and down change the speed, left and right seek,
+// home restarts and escape returns to the menu
+fn playback_controls(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut time: ResMut<Time>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        if time.is_paused() {
+            time.unpause();
+        } else {
+            time.pause();
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        let speed = (time.relative_speed() * 2.0).min(MAX_PLAYBACK_SPEED);
+        time.set_relative_speed(speed);
+    }
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        let speed = (time.relative_speed() / 2.0).max(MIN_PLAYBACK_SPEED);
+        time.set_relative_speed(speed);
+    }
+
+    let seek_steps = (SEEK_SECONDS / PHYSICS_TIMESTEP) as u32;
+    if keyboard_input.just_pressed(KeyCode::Right) {
+        let step = playback.step() + seek_steps;
+        playback.seek(step);
+    }
+    if keyboard_input.just_pressed(KeyCode::Left) {
+        let step = playback.step().saturating_sub(seek_steps);
+        playback.seek(step);
+    }
+    if keyboard_input.just_pressed(KeyCode::Home) {
+        playback.seek(0);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(AppState::Menu);
+    }
+}
+
+fn update_playback_text(
+    mut text_query: Query<&mut Text, With<PlaybackText>>,
+    playback: Res<ReplayPlayback>,
+    time: Res<Time>,
+) {
+    let status = if playback.is_finished() {
+        "Finished".to_string()
+    } else if time.is_paused() {
+        "Paused".to_string()
+    } else {
+        format!("{:.2}x", time.relative_speed())
+    };
+
+    let mut text = text_query.single_mut();
+    text.sections[0].value = format!(
+        "Replay  {}  {} / {}",
+        status,
+        format_steps(playback.step()),
+        format_steps(playback.step_count())
+    );
+}
+
+fn format_steps(steps: u32) -> String {
+    let seconds = (steps as f32 * PHYSICS_TIMESTEP) as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn cleanup_playback(
+    mut commands: Commands,
+    playback_data: Res<PlaybackData>,
+    mut time: ResMut<Time>,
+) {
+    commands.entity(playback_data.0).despawn_recursive();
+    commands.remove_resource::<PlaybackData>();
+
+    // Leave the clock as we found it
+    time.unpause();
+    time.set_relative_speed(1.0);
+}
@@ -0,0 +1,85 @@
+//! Players can steer their paddle with the mouse or by dragging a finger
+//! across the screen, which makes the game playable on touch devices. The
+//! paddle chases the height of the pointer, but never any faster than the
+//! keys would move it.
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::paddle::Side;
+
+pub struct PointerPlugin;
+
+impl Plugin for PointerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Pointers>();
+    }
+}
+
+/// A pointer that a player's paddle can follow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Pointer {
+    /// Follows the mouse cursor wherever it is on the board
+    Mouse,
+    /// Follows a finger touching the paddle's half of the board
+    Touch,
+}
+
+impl Pointer {
+    /// Cycles through following the keys alone, the mouse and touches
+    pub fn next(pointer: Option<Pointer>) -> Option<Pointer> {
+        match pointer {
+            None => Some(Pointer::Mouse),
+            Some(Pointer::Mouse) => Some(Pointer::Touch),
+            Some(Pointer::Touch) => None,
+        }
+    }
+}
+
+impl fmt::Display for Pointer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Pointer::Mouse => "Mouse",
+            Pointer::Touch => "Touch",
+        })
+    }
+}
+
+/// Where the pointers are on the board, in world coordinates. Filled in by
+/// whoever owns the window, and left empty when playing without one.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct Pointers {
+    /// The mouse cursor, while it is over the window
+    pub mouse: Option<Vec2>,
+    /// Every finger currently touching the screen
+    pub touches: Vec<Vec2>,
+}
+
+impl Pointers {
+    /// The height that a paddle on `side` following `pointer` should head to
+    pub fn target(&self, pointer: Pointer, side: Side) -> Option<f32> {
+        match pointer {
+            Pointer::Mouse => self.mouse.map(|mouse| mouse.y),
+            // Each player drags on their own half, so that two players can
+            // share a screen
+            Pointer::Touch => self
+                .touches
+                .iter()
+                .find(|touch| match side {
+                    Side::Left => touch.x < 0.0,
+                    Side::Right => touch.x >= 0.0,
+                })
+                .map(|touch| touch.y),
+        }
+    }
+}
+
+/// How a paddle at `paddle_y` should move to reach `target_y`, from -1 to 1,
+/// given how far it can travel in a single step
+pub fn follow(target_y: f32, paddle_y: f32, step_distance: f32) -> f32 {
+    if step_distance <= 0.0 {
+        return 0.0;
+    }
+    ((target_y - paddle_y) / step_distance).clamp(-1.0, 1.0)
+}
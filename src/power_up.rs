@@ -0,0 +1,441 @@
+//! Power-ups that turn up on the board now and then when the rules ask for
+//! them. The ball picks one up by passing through it, and it goes to the side
+//! whose paddle hit the ball last. What each kind does, and for how long, is
+//! set out in `DEFINITIONS`.
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::arena::{Obstacle, Segment, WALL_THICKNESS};
+use crate::ball::{self, ball_bounds, Ball, BALL_SIZE};
+use crate::game::{GameData, MatchClock};
+use crate::match_in_progress;
+use crate::paddle::{PaddleHeight, Side};
+use crate::physics::{penetration, segment_penetration, PhysicsSet, Position};
+use crate::rng::GameRng;
+use crate::rules::MatchRules;
+use crate::WINDOW_WIDTH;
+
+/// Width and height of a power-up waiting on the board
+pub const POWER_UP_SIZE: f32 = 40.;
+
+// Spots tried for a new power-up before giving up on finding it room
+const SPAWN_ATTEMPTS: usize = 10;
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActivePowerUps>()
+            .add_event::<PowerUpCollected>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    expire_power_ups,
+                    spawn_power_ups,
+                    collect_power_ups,
+                    resize_paddles,
+                )
+                    .chain()
+                    .in_set(PhysicsSet::PowerUps)
+                    .run_if(match_in_progress),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PowerUpKind {
+    /// Makes the collecting side's paddle taller
+    Grow,
+    /// Makes the other side's paddle shorter
+    Shrink,
+    /// Speeds up every ball in play
+    FastBall,
+    /// Slows down every ball in play
+    SlowMotion,
+    /// Sends extra balls off from the center
+    Multiball,
+    /// Makes the balls that the collecting side hits curve as they go
+    Curve,
+    /// Turns back balls that would have scored on the collecting side
+    Shield,
+}
+
+impl PowerUpKind {
+    pub fn definition(self) -> &'static PowerUpDefinition {
+        DEFINITIONS
+            .iter()
+            .find(|definition| definition.kind == self)
+            .expect("Every kind of power-up should be defined")
+    }
+}
+
+/// How a kind of power-up behaves
+#[derive(Debug, Clone, Copy)]
+pub struct PowerUpDefinition {
+    pub kind: PowerUpKind,
+    /// Seconds that the power-up lasts once collected, or 0 for those that
+    /// happen all at once
+    pub duration: f32,
+    /// How strong the power-up is. This is the factor that paddles are
+    /// resized by or that balls are sped up by, the number of balls added,
+    /// the radians a second that balls turn by, or the number of balls that a
+    /// shield turns back before it breaks.
+    pub strength: f32,
+    /// How likely the power-up is to be put on the board, against the others
+    pub weight: u32,
+    /// Short name shown on the power-up's icon
+    pub label: &'static str,
+    pub color: Color,
+}
+
+pub const DEFINITIONS: [PowerUpDefinition; 7] = [
+    PowerUpDefinition {
+        kind: PowerUpKind::Grow,
+        duration: 10.0,
+        strength: 1.5,
+        weight: 3,
+        label: "+",
+        color: Color::GREEN,
+    },
+    PowerUpDefinition {
+        kind: PowerUpKind::Shrink,
+        duration: 10.0,
+        strength: 0.6,
+        weight: 3,
+        label: "-",
+        color: Color::RED,
+    },
+    PowerUpDefinition {
+        kind: PowerUpKind::FastBall,
+        duration: 6.0,
+        strength: 1.4,
+        weight: 2,
+        label: ">>",
+        color: Color::ORANGE,
+    },
+    PowerUpDefinition {
+        kind: PowerUpKind::SlowMotion,
+        duration: 6.0,
+        strength: 0.6,
+        weight: 2,
+        label: "<<",
+        color: Color::CYAN,
+    },
+    PowerUpDefinition {
+        kind: PowerUpKind::Multiball,
+        duration: 0.0,
+        strength: 1.0,
+        weight: 2,
+        label: "oo",
+        color: Color::YELLOW,
+    },
+    PowerUpDefinition {
+        kind: PowerUpKind::Curve,
+        duration: 10.0,
+        strength: 1.2,
+        weight: 2,
+        label: "~",
+        color: Color::PINK,
+    },
+    PowerUpDefinition {
+        kind: PowerUpKind::Shield,
+        duration: 15.0,
+        strength: 1.0,
+        weight: 1,
+        label: "[]",
+        color: Color::BLUE,
+    },
+];
+
+/// A power-up waiting on the board to be picked up
+#[derive(Component, Debug)]
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+    /// Seconds left until it disappears
+    pub remaining: f32,
+}
+
+/// Sent whenever the ball picks up a power-up
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PowerUpCollected {
+    pub kind: PowerUpKind,
+    /// The side that the power-up was credited to
+    pub side: Side,
+}
+
+/// A power-up that has been collected and is still in effect
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActivePowerUp {
+    pub kind: PowerUpKind,
+    /// The side that collected it
+    pub side: Side,
+    /// Seconds left until it wears off
+    pub remaining: f32,
+    /// Balls that a shield can still turn back
+    pub charges: u32,
+}
+
+/// The power-ups in effect, which start over with every match
+#[derive(Resource, Debug, Default, Clone)]
+pub struct ActivePowerUps(Vec<ActivePowerUp>);
+
+impl ActivePowerUps {
+    /// Puts a power-up into effect for `side`. Collecting one that is already
+    /// in effect starts it over rather than doubling it up.
+    pub fn activate(&mut self, kind: PowerUpKind, side: Side) {
+        let definition = kind.definition();
+        if definition.duration <= 0.0 {
+            return;
+        }
+        let active = ActivePowerUp {
+            kind,
+            side,
+            remaining: definition.duration,
+            charges: definition.strength as u32,
+        };
+        match self.find_mut(kind, side) {
+            Some(existing) => *existing = active,
+            None => self.0.push(active),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ActivePowerUp> {
+        self.0.iter()
+    }
+
+    pub fn is_active(&self, kind: PowerUpKind, side: Side) -> bool {
+        self.0
+            .iter()
+            .any(|active| active.kind == kind && active.side == side)
+    }
+
+    /// How much taller than the rules make it the paddle on `side` is
+    pub fn paddle_height_multiplier(&self, side: Side) -> f32 {
+        self.0
+            .iter()
+            .filter_map(|active| match active.kind {
+                PowerUpKind::Grow if active.side == side => Some(active.kind),
+                PowerUpKind::Shrink if active.side != side => Some(active.kind),
+                _ => None,
+            })
+            .map(|kind| kind.definition().strength)
+            .product()
+    }
+
+    /// How much faster than normal the balls move
+    pub fn ball_time_scale(&self) -> f32 {
+        self.0
+            .iter()
+            .filter(|active| matches!(active.kind, PowerUpKind::FastBall | PowerUpKind::SlowMotion))
+            .map(|active| active.kind.definition().strength)
+            .product()
+    }
+
+    /// How quickly the balls hit by `side` turn, in radians a second
+    pub fn curve(&self, side: Side) -> f32 {
+        if self.is_active(PowerUpKind::Curve, side) {
+            PowerUpKind::Curve.definition().strength
+        } else {
+            0.0
+        }
+    }
+
+    /// Uses up one charge of the shield on `side`, if it has one, breaking
+    /// the shield once it is out of charges
+    pub fn use_shield(&mut self, side: Side) -> bool {
+        let Some(shield) = self.find_mut(PowerUpKind::Shield, side) else {
+            return false;
+        };
+        shield.charges = shield.charges.saturating_sub(1);
+        if shield.charges == 0 {
+            self.0
+                .retain(|active| !(active.kind == PowerUpKind::Shield && active.side == side));
+        }
+        true
+    }
+
+    fn find_mut(&mut self, kind: PowerUpKind, side: Side) -> Option<&mut ActivePowerUp> {
+        self.0
+            .iter_mut()
+            .find(|active| active.kind == kind && active.side == side)
+    }
+}
+
+// Wears off the power-ups in effect, and clears away those left on the board
+// for too long
+fn expire_power_ups(
+    mut commands: Commands,
+    mut active: ResMut<ActivePowerUps>,
+    mut power_up_query: Query<(Entity, &mut PowerUp)>,
+    mut game_data: ResMut<GameData>,
+    fixed_time: Res<FixedTime>,
+) {
+    let delta_seconds = fixed_time.period.as_secs_f32();
+
+    for power_up in active.0.iter_mut() {
+        power_up.remaining -= delta_seconds;
+    }
+    active.0.retain(|power_up| power_up.remaining > 0.0);
+
+    for (entity, mut power_up) in power_up_query.iter_mut() {
+        power_up.remaining -= delta_seconds;
+        if power_up.remaining <= 0.0 {
+            commands.entity(entity).despawn();
+            game_data.remove(entity);
+        }
+    }
+}
+
+// Puts a new power-up somewhere in the middle of the board every so often,
+// as long as there is room for it
+#[allow(clippy::too_many_arguments)]
+fn spawn_power_ups(
+    mut commands: Commands,
+    power_up_query: Query<&PowerUp>,
+    wall_query: Query<&Segment>,
+    obstacle_query: Query<&Obstacle>,
+    mut game_data: ResMut<GameData>,
+    mut rng: ResMut<GameRng>,
+    clock: Res<MatchClock>,
+    fixed_time: Res<FixedTime>,
+    rules: Res<MatchRules>,
+) {
+    let Some(power_ups) = rules.power_ups else {
+        return;
+    };
+    let interval = Duration::from_secs_f32(power_ups.interval).as_nanos();
+    let before = clock.elapsed.saturating_sub(fixed_time.period).as_nanos();
+    if clock.elapsed.as_nanos() / interval == before / interval
+        || power_up_query.iter().count() >= power_ups.max_on_board as usize
+    {
+        return;
+    }
+
+    // Pick the kind by how likely each one is to turn up
+    let total_weight: u32 = DEFINITIONS.iter().map(|definition| definition.weight).sum();
+    if total_weight == 0 {
+        return;
+    }
+    let mut roll = rng.gen_range(0..total_weight);
+    let kind = DEFINITIONS
+        .iter()
+        .find(|definition| {
+            if roll < definition.weight {
+                return true;
+            }
+            roll -= definition.weight;
+            false
+        })
+        .map(|definition| definition.kind)
+        .expect("The roll should land on one of the power-ups");
+
+    // Away from the paddles, so that there is time to aim for it, and clear
+    // of the arena's walls and of anywhere its obstacles move through
+    let half_size = POWER_UP_SIZE / 2.0;
+    let (y_min, y_max) = ball_bounds();
+    let is_clear = |position: Vec2| {
+        let wall_reach = half_size * std::f32::consts::SQRT_2 + WALL_THICKNESS / 2.0;
+        wall_query
+            .iter()
+            .all(|wall| segment_penetration(position, wall.start, wall.end, wall_reach).is_none())
+            && obstacle_query.iter().all(|obstacle| {
+                penetration(
+                    position,
+                    Vec2::splat(half_size),
+                    obstacle.center,
+                    obstacle.reach(),
+                )
+                .is_none()
+            })
+    };
+    let Some(position) = (0..SPAWN_ATTEMPTS)
+        .map(|_| {
+            Vec2::new(
+                rng.gen_range(-WINDOW_WIDTH / 4.0..WINDOW_WIDTH / 4.0),
+                rng.gen_range(y_min + half_size..y_max - half_size),
+            )
+        })
+        .find(|&position| is_clear(position))
+    else {
+        return;
+    };
+    let entity = commands
+        .spawn((
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.5))),
+            Position::new(position),
+            PowerUp {
+                kind,
+                remaining: power_ups.lifetime,
+            },
+        ))
+        .id();
+    game_data.add(entity);
+}
+
+// Hands each power-up that a ball overlaps to the side that hit the ball
+// last. Balls that nobody has hit yet pass straight through.
+#[allow(clippy::too_many_arguments)]
+fn collect_power_ups(
+    mut commands: Commands,
+    ball_query: Query<(&Position, &Ball)>,
+    power_up_query: Query<(Entity, &Position, &PowerUp)>,
+    mut active: ResMut<ActivePowerUps>,
+    mut collected_event: EventWriter<PowerUpCollected>,
+    mut game_data: ResMut<GameData>,
+    mut rng: ResMut<GameRng>,
+    rules: Res<MatchRules>,
+) {
+    let reach = (BALL_SIZE + POWER_UP_SIZE) / 2.0;
+    // Spawned balls only turn up in the query once the commands are applied
+    let mut balls_added = 0;
+
+    for (entity, power_up_position, power_up) in power_up_query.iter() {
+        let collector = ball_query.iter().find_map(|(ball_position, ball)| {
+            let offset = (ball_position.current - power_up_position.current).abs();
+            (offset.x < reach && offset.y < reach)
+                .then_some(ball.last_hitter())
+                .flatten()
+        });
+        let Some(side) = collector else {
+            continue;
+        };
+
+        commands.entity(entity).despawn();
+        game_data.remove(entity);
+        collected_event.send(PowerUpCollected {
+            kind: power_up.kind,
+            side,
+        });
+        match power_up.kind {
+            PowerUpKind::Multiball => {
+                // No more balls than multiball allows, going by its defaults
+                // when the rules leave multiball out
+                let max_balls = rules.multiball.unwrap_or_default().max_balls as usize;
+                let room = max_balls.saturating_sub(ball_query.iter().count() + balls_added);
+                let new_balls = (power_up.kind.definition().strength as usize).min(room);
+                for _ in 0..new_balls {
+                    ball::spawn_extra_ball(&mut commands, &mut game_data, &mut rng, &rules);
+                }
+                balls_added += new_balls;
+            }
+            kind => active.activate(kind, side),
+        }
+    }
+}
+
+// Grows and shrinks the paddles along with the power-ups in effect
+fn resize_paddles(
+    mut paddle_query: Query<(&mut PaddleHeight, &Side)>,
+    active: Res<ActivePowerUps>,
+    rules: Res<MatchRules>,
+) {
+    for (mut height, side) in paddle_query.iter_mut() {
+        let new_height = rules.paddle_height * active.paddle_height_multiplier(*side);
+        // Left alone when it stays the same, so that sprites only follow changes
+        if height.0 != new_height {
+            height.0 = new_height;
+        }
+    }
+}
@@ -0,0 +1,416 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::arena::ArenaError;
+use crate::game::{restart_match, setup_game, MatchSetup};
+use crate::paddle::{Paddle, PaddleInput, ServeInput, Side};
+use crate::physics::PhysicsSet;
+use crate::rng::GameRng;
+use crate::rules::{MatchRules, RulesError};
+use crate::score::{update_score, ScoreChanged};
+use crate::{match_in_progress, AppState};
+
+// Bumped whenever the layout of a replay file changes, or a rule missing from
+// older files would be filled in with a default that plays differently
+const REPLAY_VERSION: u32 = 7;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::Replay),
+            use_recorded_rules.before(setup_game),
+        )
+        .add_systems(
+            OnEnter(AppState::Game),
+            // The seed is only known once the match has been set up
+            (apply_deferred, start_recording).chain().after(setup_game),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                // Paddle inputs are final once the paddles start moving
+                record_inputs.in_set(PhysicsSet::Paddles),
+                record_scores.in_set(PhysicsSet::Score).after(update_score),
+            )
+                // Nothing is recorded while the match is paused
+                .run_if(in_state(AppState::Game).and_then(match_in_progress)),
+        )
+        // Matches that are restarted or quit before the end are not kept
+        .add_systems(OnEnter(AppState::End), finish_recording)
+        .add_systems(OnEnter(AppState::Menu), discard_recording)
+        .add_systems(
+            FixedUpdate,
+            (
+                play_back_inputs.in_set(PhysicsSet::Input),
+                check_played_back_scores
+                    .in_set(PhysicsSet::Score)
+                    .after(update_score),
+                // Only move on once the whole step has been simulated, as
+                // the playback counts as finished from then on
+                advance_playback.in_set(PhysicsSet::Victory),
+            )
+                .run_if(in_state(AppState::Replay).and_then(match_in_progress)),
+        )
+        .add_systems(Update, seek_replay.run_if(in_state(AppState::Replay)))
+        .add_systems(OnExit(AppState::Replay), stop_playback);
+    }
+}
+
+/// A recording of an entire match. Since the simulation is deterministic,
+/// the seed, the setup and the inputs of both paddles on every step are all
+/// that is needed to play the match out again exactly.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    version: u32,
+    setup: MatchSetup,
+    rules: MatchRules,
+    inputs: Vec<InputRun>,
+    scores: Vec<ScoreEvent>,
+}
+
+// A stretch of steps on which both paddles were given the same inputs
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct InputRun {
+    steps: u32,
+    inputs: StepInputs,
+}
+
+// What both paddles were asked to do on a single step
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+struct StepInputs {
+    left: f32,
+    right: f32,
+    left_serve: bool,
+    right_serve: bool,
+}
+
+/// A point that was scored during the recorded match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreEvent {
+    /// The step on which the point was scored
+    pub step: u32,
+    pub left_score: u32,
+    pub right_score: u32,
+}
+
+impl Replay {
+    fn new(setup: MatchSetup, rules: MatchRules) -> Self {
+        Replay {
+            version: REPLAY_VERSION,
+            setup,
+            rules,
+            inputs: Vec::new(),
+            scores: Vec::new(),
+        }
+    }
+
+    /// The setup of the recorded match, which always includes its seed
+    pub fn setup(&self) -> &MatchSetup {
+        &self.setup
+    }
+
+    /// The rules the recorded match was played under
+    pub fn rules(&self) -> &MatchRules {
+        &self.rules
+    }
+
+    pub fn scores(&self) -> &[ScoreEvent] {
+        &self.scores
+    }
+
+    /// How many simulation steps the recorded match lasted
+    pub fn step_count(&self) -> u32 {
+        self.inputs.iter().map(|run| run.steps).sum()
+    }
+
+    fn push_inputs(&mut self, inputs: StepInputs) {
+        match self.inputs.last_mut() {
+            Some(run) if run.inputs == inputs => run.steps += 1,
+            _ => self.inputs.push(InputRun { steps: 1, inputs }),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Replay, ReplayError> {
+        let replay: Replay = ron::from_str(&fs::read_to_string(path)?)?;
+        if replay.version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(replay.version));
+        }
+        // Files may have been edited by hand since, so they are held to the
+        // same checks as the rules and arenas they were played with
+        replay.rules.validate().map_err(ReplayError::InvalidRules)?;
+        replay
+            .setup
+            .arena
+            .validate()
+            .map_err(ReplayError::InvalidArena)?;
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ReplayError> {
+        let path = path.as_ref();
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        fs::write(path, ron::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    UnsupportedVersion(u32),
+    InvalidRules(RulesError),
+    InvalidArena(ArenaError),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(error) => write!(f, "could not access the replay file: {error}"),
+            ReplayError::Parse(error) => write!(f, "the replay file is malformed: {error}"),
+            ReplayError::Serialize(error) => write!(f, "could not write the replay: {error}"),
+            ReplayError::UnsupportedVersion(version) => write!(
+                f,
+                "the replay was recorded with version {version}, but only version {REPLAY_VERSION} is supported"
+            ),
+            ReplayError::InvalidRules(error) => write!(f, "the replay's rules are invalid: {error}"),
+            ReplayError::InvalidArena(error) => write!(f, "the replay's arena is invalid: {error}"),
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for ReplayError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        ReplayError::Parse(error)
+    }
+}
+
+impl From<ron::Error> for ReplayError {
+    fn from(error: ron::Error) -> Self {
+        ReplayError::Serialize(error)
+    }
+}
+
+// Collects the replay of the match currently being played
+#[derive(Resource)]
+struct ReplayRecorder(Replay);
+
+/// The replay of the most recently finished match
+#[derive(Resource)]
+pub struct LastReplay(pub Replay);
+
+/// Plays a replay back while in `AppState::Replay`. Insert this before
+/// switching to the state.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    replay: Replay,
+    // The inputs for every step, unpacked from their runs
+    inputs: Vec<StepInputs>,
+    step: u32,
+    seek_target: Option<u32>,
+    // The rules that were in place before the replay swapped in its own
+    configured_rules: Option<MatchRules>,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        let inputs = replay
+            .inputs
+            .iter()
+            .flat_map(|run| (0..run.steps).map(|_| run.inputs))
+            .collect();
+        ReplayPlayback {
+            replay,
+            inputs,
+            step: 0,
+            seek_target: None,
+            configured_rules: None,
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// The next step that will be played back
+    pub fn step(&self) -> u32 {
+        self.step
+    }
+
+    pub fn step_count(&self) -> u32 {
+        self.inputs.len() as u32
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step >= self.step_count()
+    }
+
+    /// Jumps to the given step by simulating the match up to it. Seeking
+    /// backwards replays the match again from the very beginning.
+    pub fn seek(&mut self, step: u32) {
+        self.seek_target = Some(step.min(self.step_count()));
+    }
+}
+
+fn start_recording(
+    mut commands: Commands,
+    match_setup: Res<MatchSetup>,
+    rng: Res<GameRng>,
+    rules: Res<MatchRules>,
+    paddle_query: Query<(&Paddle, &Side)>,
+) {
+    let is_controlled = |side| {
+        paddle_query.iter().any(|(paddle, paddle_side)| {
+            *paddle_side == side && matches!(paddle, Paddle::Controlled)
+        })
+    };
+    commands.insert_resource(ReplayRecorder(Replay::new(
+        MatchSetup {
+            seed: Some(rng.seed()),
+            left_controlled: is_controlled(Side::Left),
+            right_controlled: is_controlled(Side::Right),
+            ..match_setup.clone()
+        },
+        rules.clone(),
+    )));
+}
+
+fn record_inputs(
+    mut recorder: ResMut<ReplayRecorder>,
+    paddle_query: Query<(&PaddleInput, &ServeInput, &Side)>,
+) {
+    let mut inputs = StepInputs::default();
+    for (input, serve_input, side) in paddle_query.iter() {
+        match side {
+            Side::Left => {
+                inputs.left = input.0;
+                inputs.left_serve = serve_input.0;
+            }
+            Side::Right => {
+                inputs.right = input.0;
+                inputs.right_serve = serve_input.0;
+            }
+        }
+    }
+    recorder.0.push_inputs(inputs);
+}
+
+fn record_scores(
+    mut recorder: ResMut<ReplayRecorder>,
+    mut change_events: EventReader<ScoreChanged>,
+) {
+    for event in change_events.iter() {
+        // The inputs for the current step have already been recorded
+        let step = recorder.0.step_count() - 1;
+        recorder.0.scores.push(ScoreEvent {
+            step,
+            left_score: event.0.left_score,
+            right_score: event.0.right_score,
+        });
+    }
+}
+
+fn finish_recording(mut commands: Commands, recorder: Option<Res<ReplayRecorder>>) {
+    if let Some(recorder) = recorder {
+        commands.insert_resource(LastReplay(recorder.0.clone()));
+        commands.remove_resource::<ReplayRecorder>();
+    }
+}
+
+fn discard_recording(mut commands: Commands) {
+    commands.remove_resource::<ReplayRecorder>();
+}
+
+fn play_back_inputs(
+    playback: Res<ReplayPlayback>,
+    mut paddle_query: Query<(&mut PaddleInput, &mut ServeInput, &Side)>,
+) {
+    let Some(inputs) = playback.inputs.get(playback.step as usize) else {
+        return;
+    };
+
+    for (mut input, mut serve_input, side) in paddle_query.iter_mut() {
+        (input.0, serve_input.0) = match side {
+            Side::Left => (inputs.left, inputs.left_serve),
+            Side::Right => (inputs.right, inputs.right_serve),
+        };
+    }
+}
+
+fn advance_playback(mut playback: ResMut<ReplayPlayback>) {
+    playback.step += 1;
+}
+
+// Warn when the replay stops matching the recording, which means the
+// simulation is no longer deterministic
+fn check_played_back_scores(
+    playback: Res<ReplayPlayback>,
+    mut change_events: EventReader<ScoreChanged>,
+) {
+    for event in change_events.iter() {
+        let scored = ScoreEvent {
+            step: playback.step,
+            left_score: event.0.left_score,
+            right_score: event.0.right_score,
+        };
+        if !playback.replay.scores.contains(&scored) {
+            warn!("Replay has desynced, {scored:?} was never recorded");
+        }
+    }
+}
+
+fn seek_replay(world: &mut World) {
+    let Some(target) = world.resource_mut::<ReplayPlayback>().seek_target.take() else {
+        return;
+    };
+
+    if target < world.resource::<ReplayPlayback>().step {
+        world.resource_mut::<ReplayPlayback>().step = 0;
+        restart_match(world);
+    }
+
+    // Simulate as fast as possible until we reach the requested step
+    loop {
+        let playback = world.resource::<ReplayPlayback>();
+        if playback.step >= target || playback.is_finished() {
+            break;
+        }
+        world.run_schedule(FixedUpdate);
+    }
+}
+
+// Play the replay under the rules it was recorded with
+fn use_recorded_rules(mut playback: ResMut<ReplayPlayback>, mut rules: ResMut<MatchRules>) {
+    let recorded_rules = playback.replay.rules.clone();
+    playback.configured_rules = Some(std::mem::replace(&mut *rules, recorded_rules));
+}
+
+fn stop_playback(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    mut rules: ResMut<MatchRules>,
+) {
+    if let Some(configured_rules) = playback.configured_rules.take() {
+        *rules = configured_rules;
+    }
+    commands.remove_resource::<ReplayPlayback>();
+}
@@ -0,0 +1,375 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::paddle::Side;
+use crate::score::Score;
+use crate::{UI_HEIGHT, WINDOW_HEIGHT};
+
+/// The tunable rules of a match. Loaded from the settings file when the game
+/// starts, and stored in replays so that they play out under the same rules.
+///
+/// Any field left out of the settings file keeps its default value.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MatchRules {
+    /// How the winner of the match is decided
+    pub victory: VictoryCondition,
+    /// Speed of the ball in pixels per second after it is hit by a paddle
+    pub ball_speed: f32,
+    /// Fraction of `ball_speed` that the ball is served at
+    pub ball_serve_multiplier: f32,
+    /// Speed in pixels per second that the ball gains with each hit of a
+    /// rally, on top of `ball_speed`
+    pub ball_speed_increase: f32,
+    /// Fastest that the ball can be hit, in pixels per second
+    pub ball_max_speed: f32,
+    /// Who serves the ball after a point
+    pub serve_rule: ServeRule,
+    /// Seconds that the ball is held for before it is served
+    pub serve_countdown: f32,
+    /// Lets players serve with their serve key once the countdown is over,
+    /// holding the ball on their paddle until they do. The computer and
+    /// other controllers still serve as soon as the countdown is over.
+    pub manual_serve: bool,
+    /// Speed of the paddles in pixels per second
+    pub paddle_speed: f32,
+    /// Height of the paddles in pixels
+    pub paddle_height: f32,
+    /// Makes the paddles speed up and slow down rather than start and stop
+    /// at once. Left out, they move at `paddle_speed` or not at all.
+    pub paddle_momentum: Option<PaddleMomentum>,
+    /// Fraction of a paddle's vertical speed that is passed on to the ball
+    /// when the paddle hits it, bending the angle that it is sent off at
+    pub paddle_spin: f32,
+    /// Adds more balls to the match as it goes on. Left out, there is only
+    /// ever the one ball.
+    pub multiball: Option<Multiball>,
+    /// Scatters power-ups over the board for the ball to pick up. Left out,
+    /// there are none.
+    pub power_ups: Option<PowerUps>,
+}
+
+/// How paddles gather and lose speed. They never move faster than the
+/// match's `paddle_speed`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PaddleMomentum {
+    /// How quickly a paddle speeds up while it is being moved, in pixels
+    /// per second squared
+    pub acceleration: f32,
+    /// How quickly a paddle slows down once it is let go of, in pixels per
+    /// second squared
+    pub friction: f32,
+}
+
+impl Default for PaddleMomentum {
+    fn default() -> Self {
+        PaddleMomentum {
+            acceleration: 3000.,
+            friction: 2500.,
+        }
+    }
+}
+
+/// When extra balls join the match. Extra balls are sent off from the center
+/// as soon as they are added, and leave the match again once they score.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Multiball {
+    /// Seconds of play between extra balls being added, or 0 to never add
+    /// them on a timer
+    pub interval: f32,
+    /// Hits in a single rally after which another ball is added, or 0 to
+    /// never add them for long rallies
+    pub rally_hits: u32,
+    /// Most balls that can be in play at once, the served ball included
+    pub max_balls: u32,
+}
+
+impl Default for Multiball {
+    fn default() -> Self {
+        Multiball {
+            interval: 20.,
+            rally_hits: 10,
+            max_balls: 3,
+        }
+    }
+}
+
+/// How often power-ups turn up on the board. What each one does is set out
+/// in `power_up::DEFINITIONS`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PowerUps {
+    /// Seconds of play between power-ups being put on the board
+    pub interval: f32,
+    /// Most power-ups that can be waiting on the board at once
+    pub max_on_board: u32,
+    /// Seconds that a power-up waits on the board before it disappears
+    pub lifetime: f32,
+}
+
+impl Default for PowerUps {
+    fn default() -> Self {
+        PowerUps {
+            interval: 8.,
+            max_on_board: 2,
+            lifetime: 12.,
+        }
+    }
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        MatchRules {
+            victory: VictoryCondition::FirstTo(10),
+            ball_speed: 800.,
+            ball_serve_multiplier: 0.65,
+            ball_speed_increase: 20.,
+            ball_max_speed: 1200.,
+            serve_rule: ServeRule::Alternate,
+            serve_countdown: 3.,
+            manual_serve: false,
+            paddle_speed: 500.,
+            paddle_height: 120.,
+            paddle_momentum: None,
+            paddle_spin: 0.,
+            multiball: None,
+            power_ups: None,
+        }
+    }
+}
+
+impl MatchRules {
+    /// Reads and validates the rules from a RON file
+    pub fn load(path: impl AsRef<Path>) -> Result<MatchRules, RulesError> {
+        let rules: MatchRules = ron::from_str(&fs::read_to_string(path)?)?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Checks that every rule is within a range that the game can be played with
+    pub fn validate(&self) -> Result<(), RulesError> {
+        let playfield_height = WINDOW_HEIGHT - UI_HEIGHT;
+
+        self.victory.validate()?;
+        check_range("ball_speed", self.ball_speed, 1.0, 10_000.0)?;
+        check_range(
+            "ball_serve_multiplier",
+            self.ball_serve_multiplier,
+            0.05,
+            2.0,
+        )?;
+        check_range(
+            "ball_speed_increase",
+            self.ball_speed_increase,
+            0.0,
+            1_000.0,
+        )?;
+        check_range(
+            "ball_max_speed",
+            self.ball_max_speed,
+            self.ball_speed,
+            10_000.0,
+        )?;
+        check_range("serve_countdown", self.serve_countdown, 0.0, 10.0)?;
+        check_range("paddle_speed", self.paddle_speed, 1.0, 10_000.0)?;
+        check_range("paddle_height", self.paddle_height, 1.0, playfield_height)?;
+        if let Some(momentum) = &self.paddle_momentum {
+            check_range(
+                "paddle_momentum.acceleration",
+                momentum.acceleration,
+                1.0,
+                100_000.0,
+            )?;
+            check_range(
+                "paddle_momentum.friction",
+                momentum.friction,
+                0.0,
+                100_000.0,
+            )?;
+        }
+        check_range("paddle_spin", self.paddle_spin, 0.0, 2.0)?;
+        if let Some(multiball) = &self.multiball {
+            check_range("multiball.interval", multiball.interval, 0.0, 600.0)?;
+            check_range(
+                "multiball.rally_hits",
+                multiball.rally_hits as f32,
+                0.0,
+                1_000.0,
+            )?;
+            check_range("multiball.max_balls", multiball.max_balls as f32, 1.0, 16.0)?;
+        }
+        if let Some(power_ups) = &self.power_ups {
+            check_range("power_ups.interval", power_ups.interval, 0.5, 600.0)?;
+            check_range(
+                "power_ups.max_on_board",
+                power_ups.max_on_board as f32,
+                1.0,
+                8.0,
+            )?;
+            check_range("power_ups.lifetime", power_ups.lifetime, 0.5, 600.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Who serves the ball after a point is scored. The left side always
+/// serves first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ServeRule {
+    /// The sides take turns
+    Alternate,
+    /// The side that lost the point
+    Loser,
+    /// The side that won the point
+    Winner,
+}
+
+impl ServeRule {
+    /// The side serving next, given who served last and who scored
+    pub fn next_server(&self, last_server: Side, scorer: Side) -> Side {
+        match self {
+            ServeRule::Alternate => last_server.opponent(),
+            ServeRule::Loser => scorer.opponent(),
+            ServeRule::Winner => scorer,
+        }
+    }
+}
+
+/// The ways in which a match can be won
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VictoryCondition {
+    /// The first side to reach this many points wins
+    FirstTo(u32),
+    /// The first side to reach this many points wins, but only once they
+    /// are two points clear of the other side
+    WinByTwo(u32),
+    /// Whoever is ahead once the match clock reaches this many seconds wins.
+    /// If the scores are level, the next point wins.
+    Timed(u32),
+    /// Sets are played first to `points`, and the first side to win more
+    /// than half of the `sets` wins the match
+    BestOfSets { sets: u32, points: u32 },
+}
+
+impl VictoryCondition {
+    /// Decides who has won the match, if anyone has yet
+    pub fn winner(&self, score: &Score, elapsed: Duration) -> Option<Side> {
+        let leader = match score.left_score.cmp(&score.right_score) {
+            std::cmp::Ordering::Greater => Some(Side::Left),
+            std::cmp::Ordering::Less => Some(Side::Right),
+            std::cmp::Ordering::Equal => None,
+        };
+        let high_score = score.left_score.max(score.right_score);
+
+        match *self {
+            VictoryCondition::FirstTo(points) => leader.filter(|_| high_score >= points),
+            VictoryCondition::WinByTwo(points) => leader.filter(|_| {
+                high_score >= points && score.left_score.abs_diff(score.right_score) >= 2
+            }),
+            VictoryCondition::Timed(seconds) => {
+                leader.filter(|_| elapsed >= Duration::from_secs(seconds.into()))
+            }
+            VictoryCondition::BestOfSets { sets, .. } => {
+                let sets_needed = sets / 2 + 1;
+                if score.left_sets >= sets_needed {
+                    Some(Side::Left)
+                } else if score.right_sets >= sets_needed {
+                    Some(Side::Right)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Who has won the set being played, for matches that are played in sets
+    pub fn set_winner(&self, score: &Score) -> Option<Side> {
+        let VictoryCondition::BestOfSets { points, .. } = *self else {
+            return None;
+        };
+        if score.left_score >= points {
+            Some(Side::Left)
+        } else if score.right_score >= points {
+            Some(Side::Right)
+        } else {
+            None
+        }
+    }
+
+    fn validate(&self) -> Result<(), RulesError> {
+        let reason = match *self {
+            VictoryCondition::FirstTo(0) | VictoryCondition::WinByTwo(0) => {
+                "must need at least 1 point to win"
+            }
+            VictoryCondition::Timed(0) => "must last at least 1 second",
+            VictoryCondition::BestOfSets { sets: 0, .. } => "must play at least 1 set",
+            VictoryCondition::BestOfSets { points: 0, .. } => {
+                "must need at least 1 point to win a set"
+            }
+            _ => return Ok(()),
+        };
+        Err(RulesError::invalid("victory", reason.to_string()))
+    }
+}
+
+fn check_range(field: &'static str, value: f32, min: f32, max: f32) -> Result<(), RulesError> {
+    if (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(RulesError::invalid(
+            field,
+            format!("must be between {min} and {max}, but was {value}"),
+        ))
+    }
+}
+
+#[derive(Debug)]
+pub enum RulesError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Invalid { field: &'static str, reason: String },
+}
+
+impl RulesError {
+    fn invalid(field: &'static str, reason: String) -> Self {
+        RulesError::Invalid { field, reason }
+    }
+
+    /// Whether the settings file simply doesn't exist
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, RulesError::Io(error) if error.kind() == io::ErrorKind::NotFound)
+    }
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RulesError::Io(error) => write!(f, "could not read the settings file: {error}"),
+            RulesError::Parse(error) => write!(f, "the settings file is malformed: {error}"),
+            RulesError::Invalid { field, reason } => write!(f, "`{field}` {reason}"),
+        }
+    }
+}
+
+impl Error for RulesError {}
+
+impl From<io::Error> for RulesError {
+    fn from(error: io::Error) -> Self {
+        RulesError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for RulesError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        RulesError::Parse(error)
+    }
+}
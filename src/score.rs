@@ -1,43 +1,65 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
 
-use crate::AppState;
+use crate::match_in_progress;
+use crate::paddle::Side;
+use crate::physics::PhysicsSet;
 
 pub struct ScorePlugin;
 
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<ScoreChanged>().add_systems(
-            Update,
-            (update_score, play_sound_on_score).run_if(in_state(AppState::Game)),
-        );
+        app.add_event::<PointScored>()
+            .add_event::<ScoreChanged>()
+            .add_systems(
+                FixedUpdate,
+                update_score
+                    .in_set(PhysicsSet::Score)
+                    .run_if(match_in_progress),
+            );
     }
 }
 
-#[derive(Component, Default, Clone, Copy)]
+#[derive(Component, Debug, Default, Clone, Copy)]
 pub struct Score {
     pub right_score: u32,
     pub left_score: u32,
+    /// Sets won by each side, for matches that are played in sets
+    pub right_sets: u32,
+    pub left_sets: u32,
+}
+
+/// Sent exactly once for every point, as soon as the ball gets past a goal
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PointScored {
+    /// The side that won the point
+    pub side: Side,
+    /// How many times the ball was hit during the rally
+    pub rally_hits: u32,
+    /// How long the ball was in play, from the serve until the point
+    pub duration: Duration,
 }
 
+/// The score after a point has been added to it
 #[derive(Event)]
 pub struct ScoreChanged(pub Score);
 
-fn update_score(mut change_events: EventReader<ScoreChanged>, mut score_query: Query<&mut Score>) {
-    let mut score = score_query.get_single_mut().unwrap();
-    for event in change_events.iter() {
-        *score = event.0;
-    }
-}
-
-fn play_sound_on_score(
-    mut change_events: EventReader<ScoreChanged>,
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
+/// The only place that points are added to the score. Anything that needs to
+/// see the new score in the same step should run after this.
+pub fn update_score(
+    mut point_events: EventReader<PointScored>,
+    mut score_query: Query<&mut Score>,
+    mut change_events: EventWriter<ScoreChanged>,
 ) {
-    for _ in change_events.iter() {
-        commands.spawn(AudioBundle {
-            source: asset_server.load("sounds/score_sound.wav"),
-            settings: PlaybackSettings::DESPAWN,
-        });
+    let mut score = score_query
+        .get_single_mut()
+        .expect("Only one score object should exist at a time!");
+    for point in point_events.iter() {
+        match point.side {
+            Side::Left => score.left_score += 1,
+            Side::Right => score.right_score += 1,
+        }
+        change_events.send(ScoreChanged(*score));
     }
 }
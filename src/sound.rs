@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+
+use bevy_pong::ball::{PaddleHit, WallHit};
+use bevy_pong::score::ScoreChanged;
+use bevy_pong::AppState;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (play_sound_on_hit, play_sound_on_score)
+                .run_if(in_state(AppState::Game).or_else(in_state(AppState::Replay))),
+        );
+    }
+}
+
+// Sound effect upon bouncing off of a paddle or a wall
+fn play_sound_on_hit(
+    mut paddle_hit_events: EventReader<PaddleHit>,
+    mut wall_hit_events: EventReader<WallHit>,
+    sound_query: Query<(), With<AudioSink>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let hit_count = paddle_hit_events.iter().count() + wall_hit_events.iter().count();
+    // Don't stack sounds on top of one another
+    if hit_count > 0 && sound_query.is_empty() {
+        commands.spawn(AudioBundle {
+            source: asset_server.load("sounds/hit_sound.wav"),
+            settings: PlaybackSettings::DESPAWN,
+        });
+    }
+}
+
+fn play_sound_on_score(
+    mut change_events: EventReader<ScoreChanged>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    // Seeking through a replay can score many points at once, but only
+    // one sound should be played for them
+    if change_events.iter().count() > 0 {
+        commands.spawn(AudioBundle {
+            source: asset_server.load("sounds/score_sound.wav"),
+            settings: PlaybackSettings::DESPAWN,
+        });
+    }
+}
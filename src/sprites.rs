@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+
+use bevy_pong::arena::{Obstacle, Segment, WALL_THICKNESS};
+use bevy_pong::ball::Ball;
+use bevy_pong::paddle::{Paddle, PaddleHeight, Side, PADDLE_WIDTH};
+use bevy_pong::power_up::{PowerUp, POWER_UP_SIZE};
+
+pub struct SpritesPlugin;
+
+impl Plugin for SpritesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (
+                add_ball_sprites,
+                add_paddle_sprites,
+                resize_paddle_sprites,
+                add_power_up_sprites,
+                add_arena_sprites,
+            ),
+        );
+    }
+}
+
+// The game spawns its entities without any visuals, so dress them up here
+fn add_ball_sprites(
+    mut commands: Commands,
+    ball_query: Query<Entity, Added<Ball>>,
+    asset_server: Res<AssetServer>,
+) {
+    for entity in ball_query.iter() {
+        commands
+            .entity(entity)
+            .insert(create_sprite(asset_server.load("sprites/Ball.png"), None));
+    }
+}
+
+fn add_paddle_sprites(
+    mut commands: Commands,
+    paddle_query: Query<(Entity, &Side, &PaddleHeight), Added<Paddle>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, side, height) in paddle_query.iter() {
+        // Stretch the sprite to however tall the paddle is
+        let paddle_size = Vec2::new(PADDLE_WIDTH, height.0);
+        let texture = match side {
+            Side::Left => asset_server.load("sprites/Computer.png"),
+            Side::Right => asset_server.load("sprites/Player.png"),
+        };
+        commands
+            .entity(entity)
+            .insert(create_sprite(texture, Some(paddle_size)));
+    }
+}
+
+// Paddles can be grown and shrunk by power-ups partway through a match
+fn resize_paddle_sprites(
+    mut paddle_query: Query<(&mut Sprite, &PaddleHeight), Changed<PaddleHeight>>,
+) {
+    for (mut sprite, height) in paddle_query.iter_mut() {
+        sprite.custom_size = Some(Vec2::new(PADDLE_WIDTH, height.0));
+    }
+}
+
+// Power-ups are plain squares in the color of their kind
+fn add_power_up_sprites(
+    mut commands: Commands,
+    power_up_query: Query<(Entity, &PowerUp), Added<PowerUp>>,
+) {
+    for (entity, power_up) in power_up_query.iter() {
+        let (mut sprite, texture, visibility) =
+            create_sprite(Handle::default(), Some(Vec2::splat(POWER_UP_SIZE)));
+        sprite.color = power_up.kind.definition().color;
+        commands
+            .entity(entity)
+            .insert((sprite, texture, visibility));
+    }
+}
+
+// Walls and obstacles are plain bars and boxes, laid along the transforms
+// that the arena was spawned with. Walls run on past their ends by as much as
+// the ball is kept away from them.
+fn add_arena_sprites(
+    mut commands: Commands,
+    wall_query: Query<(Entity, &Segment), Added<Segment>>,
+    obstacle_query: Query<(Entity, &Obstacle), Added<Obstacle>>,
+) {
+    let sizes = wall_query
+        .iter()
+        .map(|(entity, wall)| {
+            (
+                entity,
+                Vec2::new(wall.length() + WALL_THICKNESS, WALL_THICKNESS),
+            )
+        })
+        .chain(
+            obstacle_query
+                .iter()
+                .map(|(entity, obstacle)| (entity, obstacle.half_size * 2.0)),
+        );
+    for (entity, size) in sizes {
+        let (mut sprite, texture, visibility) = create_sprite(Handle::default(), Some(size));
+        sprite.color = Color::GRAY;
+        commands
+            .entity(entity)
+            .insert((sprite, texture, visibility));
+    }
+}
+
+fn create_sprite(
+    texture: Handle<Image>,
+    custom_size: Option<Vec2>,
+) -> (Sprite, Handle<Image>, VisibilityBundle) {
+    (
+        Sprite {
+            custom_size,
+            ..default()
+        },
+        texture,
+        VisibilityBundle::default(),
+    )
+}
@@ -1,20 +1,34 @@
-use std::time::Duration;
-
-use crate::{AppState, MAIN_FONT, UI_HEIGHT};
-
-use super::score::ScoreChanged;
 use bevy::prelude::*;
+use bevy_pong::ball::{Ball, ExtraBall};
+use bevy_pong::bindings::KeyBindings;
+use bevy_pong::game::MatchClock;
+use bevy_pong::paddle::Side;
+use bevy_pong::power_up::{ActivePowerUps, PowerUpKind, DEFINITIONS};
+use bevy_pong::rules::{MatchRules, VictoryCondition};
+use bevy_pong::score::Score;
+use bevy_pong::{AppState, UI_HEIGHT};
+
+use crate::MAIN_FONT;
 
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AppState::Game), setup_game_ui)
+            .add_systems(OnEnter(AppState::Replay), setup_game_ui)
             .add_systems(
                 Update,
-                (update_score_text, update_timer).run_if(in_state(AppState::Game)),
+                (
+                    update_score_text,
+                    update_timer,
+                    update_rally_text,
+                    update_countdown_text,
+                    update_power_up_icons,
+                )
+                    .run_if(in_state(AppState::Game).or_else(in_state(AppState::Replay))),
             )
-            .add_systems(OnExit(AppState::Game), cleanup_game_ui);
+            .add_systems(OnExit(AppState::Game), cleanup_game_ui)
+            .add_systems(OnExit(AppState::Replay), cleanup_game_ui);
     }
 }
 
@@ -25,28 +39,38 @@ pub enum ScoreText {
 }
 
 #[derive(Component)]
-struct TimerText {
-    timer: Timer,
-    minutes: u32,
-    seconds: u32,
-}
+struct TimerText;
 
-impl TimerText {
-    fn new() -> TimerText {
-        TimerText {
-            timer: Timer::new(Duration::from_secs(1), TimerMode::Repeating),
-            minutes: 0,
-            seconds: 0,
-        }
-    }
+// Counts down to the next serve in the middle of the board
+#[derive(Component)]
+struct CountdownText;
+
+// One of the icons under a side's score, shown while that side has the
+// power-up in effect
+#[derive(Component)]
+struct PowerUpIcon {
+    side: Side,
+    kind: PowerUpKind,
 }
 
+// Seconds before a power-up wears off that its icon starts blinking
+const ICON_BLINK_SECONDS: f32 = 2.0;
+
 // Despawn menu when exiting game state
 #[derive(Resource)]
 struct GameUIData(Vec<Entity>);
 
-pub fn setup_game_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+pub fn setup_game_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    rules: Res<MatchRules>,
+) {
     let font = asset_server.load(MAIN_FONT);
+    // Sets are only counted when the match is played in them
+    let sets_text = match rules.victory {
+        VictoryCondition::BestOfSets { .. } => " 0",
+        _ => "",
+    };
     let game_ui = vec![
         // Spawn the top-Scorebar
         commands
@@ -84,16 +108,28 @@ pub fn setup_game_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                     // Left Scoretext
                     .with_children(|parent| {
                         parent.spawn((
-                            TextBundle::from_section(
-                                "0",
-                                TextStyle {
-                                    font: font.clone(),
-                                    font_size: 60.0,
-                                    color: Color::WHITE,
-                                },
-                            ),
+                            TextBundle::from_sections([
+                                TextSection::new(
+                                    "0",
+                                    TextStyle {
+                                        font: font.clone(),
+                                        font_size: 60.0,
+                                        color: Color::WHITE,
+                                    },
+                                ),
+                                // Sets won
+                                TextSection::new(
+                                    sets_text,
+                                    TextStyle {
+                                        font: font.clone(),
+                                        font_size: 24.0,
+                                        color: Color::GRAY,
+                                    },
+                                ),
+                            ]),
                             ScoreText::Left,
                         ));
+                        spawn_power_up_icons(parent, Side::Left, &font);
                     });
                 // Timer
                 parent.spawn((
@@ -122,8 +158,17 @@ pub fn setup_game_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                                 color: Color::WHITE,
                             },
                         ),
+                        // Hits in the current rally
+                        TextSection::new(
+                            "",
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 24.0,
+                                color: Color::GRAY,
+                            },
+                        ),
                     ]),
-                    TimerText::new(),
+                    TimerText,
                 ));
                 // Right side UI Bar
                 parent
@@ -144,19 +189,59 @@ pub fn setup_game_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                     // Right Scoretext
                     .with_children(|parent| {
                         parent.spawn((
-                            TextBundle::from_section(
-                                "0",
-                                TextStyle {
-                                    font: font.clone(),
-                                    font_size: 60.0,
-                                    color: Color::WHITE,
-                                },
-                            ),
+                            TextBundle::from_sections([
+                                TextSection::new(
+                                    "0",
+                                    TextStyle {
+                                        font: font.clone(),
+                                        font_size: 60.0,
+                                        color: Color::WHITE,
+                                    },
+                                ),
+                                // Sets won
+                                TextSection::new(
+                                    sets_text,
+                                    TextStyle {
+                                        font: font.clone(),
+                                        font_size: 24.0,
+                                        color: Color::GRAY,
+                                    },
+                                ),
+                            ]),
                             ScoreText::Right,
                         ));
+                        spawn_power_up_icons(parent, Side::Right, &font);
                     });
             })
             .id(),
+        // The serve countdown, over the middle of the board
+        commands
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    top: Val::Px(UI_HEIGHT / 2.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 120.0,
+                            color: Color::rgba(1.0, 1.0, 1.0, 0.8),
+                        },
+                    ),
+                    CountdownText,
+                ));
+            })
+            .id(),
         // The Board
         commands
             .spawn(SpriteBundle {
@@ -173,42 +258,155 @@ pub fn setup_game_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.insert_resource(GameUIData(game_ui));
 }
 
+// A row of hidden icons along the inner end of a score bar, one for every
+// kind of power-up
+fn spawn_power_up_icons(parent: &mut ChildBuilder, side: Side, font: &Handle<Font>) {
+    let (left, right) = match side {
+        Side::Left => (Val::Auto, Val::Px(8.0)),
+        Side::Right => (Val::Px(8.0), Val::Auto),
+    };
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left,
+                right,
+                column_gap: Val::Px(3.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for definition in DEFINITIONS.iter() {
+                parent
+                    .spawn((
+                        NodeBundle {
+                            style: Style {
+                                display: Display::None,
+                                width: Val::Px(22.0),
+                                height: Val::Px(22.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: definition.color.into(),
+                            ..default()
+                        },
+                        PowerUpIcon {
+                            side,
+                            kind: definition.kind,
+                        },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            definition.label,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 16.0,
+                                color: Color::BLACK,
+                            },
+                        ));
+                    });
+            }
+        });
+}
+
 fn update_score_text(
     mut text_query: Query<(&mut Text, &ScoreText)>,
-    mut change_events: EventReader<ScoreChanged>,
+    score_query: Query<&Score, Changed<Score>>,
 ) {
-    for score_changed in change_events.iter() {
-        let score = &score_changed.0;
+    for score in score_query.iter() {
         for (mut text, score_text) in text_query.iter_mut() {
-            match score_text {
-                ScoreText::Right => text.sections[0].value = format!("{}", score.right_score),
-                ScoreText::Left => text.sections[0].value = format!("{}", score.left_score),
+            let (points, sets) = match score_text {
+                ScoreText::Right => (score.right_score, score.right_sets),
+                ScoreText::Left => (score.left_score, score.left_sets),
+            };
+            text.sections[0].value = format!("{}", points);
+            if !text.sections[1].value.is_empty() {
+                text.sections[1].value = format!(" {}", sets);
             }
         }
     }
 }
 
-fn update_timer(mut text_query: Query<(&mut Text, &mut TimerText)>, time: Res<Time>) {
-    // Unwrap the timer query
-    let (mut text, mut timer_text) = text_query.single_mut();
-    // Tick the timer
-    timer_text.timer.tick(time.delta());
+fn update_timer(mut text_query: Query<&mut Text, With<TimerText>>, clock: Option<Res<MatchClock>>) {
+    let Some(clock) = clock else {
+        return;
+    };
+    let mut text = text_query.single_mut();
+
+    let seconds = clock.elapsed.as_secs();
+    text.sections[0].value = (seconds / 60).to_string();
+    text.sections[2].value = format!("{:02}", seconds % 60);
+}
 
-    // Exit if the timer has not gone off
-    if !timer_text.timer.finished() {
+// Counts the rally of the served ball up next to the timer, and keeps quiet
+// until it starts
+fn update_rally_text(
+    mut text_query: Query<&mut Text, With<TimerText>>,
+    ball_query: Query<&Ball, (Changed<Ball>, Without<ExtraBall>)>,
+) {
+    let Ok(ball) = ball_query.get_single() else {
         return;
-    }
+    };
+    let mut text = text_query.single_mut();
+
+    text.sections[3].value = match ball.rally_hits() {
+        0 => String::new(),
+        hits => format!(" x{hits}"),
+    };
+}
 
-    timer_text.seconds += 1;
-    if timer_text.seconds == 60 {
-        timer_text.seconds = 0;
-        timer_text.minutes += 1;
+// Shows the power-ups that each side has in effect, blinking the ones about
+// to wear off
+fn update_power_up_icons(
+    mut icon_query: Query<(&mut Style, &PowerUpIcon)>,
+    active: Option<Res<ActivePowerUps>>,
+) {
+    let Some(active) = active else {
+        return;
+    };
+    for (mut style, icon) in icon_query.iter_mut() {
+        let remaining = active
+            .iter()
+            .find(|power_up| power_up.kind == icon.kind && power_up.side == icon.side)
+            .map(|power_up| power_up.remaining);
+        let is_shown = match remaining {
+            Some(seconds) if seconds < ICON_BLINK_SECONDS => (seconds * 2.0).fract() < 0.5,
+            Some(_) => true,
+            None => false,
+        };
+        let display = if is_shown {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if style.display != display {
+            style.display = display;
+        }
     }
-    text.sections[0].value = timer_text.minutes.to_string();
-    text.sections[2].value = if timer_text.seconds <= 9 {
-        format!("0{}", timer_text.seconds)
-    } else {
-        timer_text.seconds.to_string()
+}
+
+// Shows the seconds left before the ball is served, and then which key to
+// press when a player has to serve it by hand
+fn update_countdown_text(
+    mut text_query: Query<&mut Text, With<CountdownText>>,
+    ball_query: Query<&Ball>,
+    bindings: Res<KeyBindings>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+    let countdown = match ball_query
+        .iter()
+        .find_map(|ball| Some((ball, ball.countdown()?)))
+    {
+        Some((_, seconds)) if seconds > 0.0 => (seconds.ceil() as u32).to_string(),
+        Some((ball, _)) => format!("{:?} to serve", bindings.keys(ball.server()).serve),
+        None => String::new(),
+    };
+    if text.sections[0].value != countdown {
+        text.sections[0].value = countdown;
     }
 }
 
@@ -0,0 +1,155 @@
+mod common;
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_pong::ai::{predict_intercept, ComputerMind, ComputerSkill, Difficulty};
+use bevy_pong::ball::{ball_bounds, Ball};
+use bevy_pong::controller::{BallState, GameSnapshot};
+use bevy_pong::game::MatchSetup;
+use bevy_pong::paddle::Side;
+use bevy_pong::rules::MatchRules;
+use bevy_pong::score::Score;
+use bevy_pong::AppState;
+use common::*;
+
+#[test]
+fn intercept_follows_a_straight_path() {
+    let intercept = predict_intercept(Vec2::ZERO, Vec2::new(-100.0, 10.0), -300.0);
+    assert_eq!(intercept, Some(30.0));
+}
+
+#[test]
+fn intercept_bounces_off_the_borders() {
+    let (y_min, y_max) = ball_bounds();
+    // Heading for a point 50 past the top border comes back down by 50
+    let start = Vec2::new(0.0, y_max - 100.0);
+    let intercept = predict_intercept(start, Vec2::new(150.0, 150.0), 150.0).unwrap();
+    assert!((intercept - (y_max - 50.0)).abs() < 0.01);
+    // And likewise off of the bottom border
+    let start = Vec2::new(0.0, y_min + 20.0);
+    let intercept = predict_intercept(start, Vec2::new(-100.0, -100.0), -60.0).unwrap();
+    assert!((intercept - (y_min + 40.0)).abs() < 0.01);
+}
+
+#[test]
+fn no_intercept_when_the_ball_moves_away() {
+    assert_eq!(
+        predict_intercept(Vec2::ZERO, Vec2::new(100.0, 0.0), -300.0),
+        None
+    );
+}
+
+#[test]
+fn impossible_computer_never_misses_a_motionless_player() {
+    let mut app = headless_app_with_setup(MatchSetup {
+        is_single_player: true,
+        seed: Some(TEST_SEED),
+        difficulty: Difficulty::Impossible,
+        ..default()
+    });
+    start_match(&mut app);
+
+    let final_score = play_match(&mut app);
+    assert_eq!(state(&app), AppState::End);
+    assert_eq!(final_score.right_score, 0);
+}
+
+#[test]
+fn easy_computer_lets_points_through() {
+    // Only the roll to miss is left to chance, so that every approach of the
+    // ball is either met exactly or stepped away from
+    let skill = ComputerSkill {
+        prediction_noise: 0.0,
+        ..Difficulty::Easy.skill()
+    };
+    let rules = MatchRules::default();
+    let mut mind = ComputerMind::new(TEST_SEED);
+    let paddle = Vec2::new(-380.0, 50.0);
+    let mut input = |velocity_x: f32| {
+        let snapshot = GameSnapshot {
+            side: Side::Left,
+            paddle,
+            opponent: None,
+            ball: BallState {
+                position: Vec2::new(0.0, paddle.y),
+                velocity: Vec2::new(velocity_x, 0.0),
+            },
+            score: Score::default(),
+            elapsed: Duration::ZERO,
+            rules: &rules,
+            delta_seconds: 1.0,
+        };
+        mind.input(&skill, &snapshot)
+    };
+
+    // The first step only notices the ball turning, the second one acts on it
+    let approaches = 1000;
+    let mut misses = 0;
+    for _ in 0..approaches {
+        input(-500.0);
+        if input(-500.0) != 0.0 {
+            misses += 1;
+        }
+        input(500.0);
+    }
+    let expected = skill.miss_chance * approaches as f64;
+    assert!(
+        (misses as f64 - expected).abs() < expected / 3.0,
+        "{misses} misses out of {approaches}"
+    );
+}
+
+// Pauses a single player match for a while just as the ball turns towards
+// the computer, while it is still taking that in, and then follows the
+// computer's paddle once the match is resumed
+fn computer_after_pause(paused_frames: usize) -> Vec<f32> {
+    let mut app = headless_app_with_setup(MatchSetup {
+        is_single_player: true,
+        seed: Some(TEST_SEED),
+        difficulty: Difficulty::Normal,
+        ..default()
+    });
+    start_match(&mut app);
+    loop {
+        let ball = app.world.query::<&Ball>().single(&app.world);
+        if !ball.is_held() && ball.velocity().x < 0.0 {
+            break;
+        }
+        app.update();
+    }
+    tap(&mut app, KeyCode::Escape);
+    step(&mut app, paused_frames);
+    tap(&mut app, KeyCode::Escape);
+
+    (0..60)
+        .map(|_| {
+            app.update();
+            paddle_position(&mut app, Side::Left).y
+        })
+        .collect()
+}
+
+#[test]
+fn computer_plays_the_same_however_long_a_pause_lasts() {
+    assert_eq!(computer_after_pause(5), computer_after_pause(300));
+}
+
+#[test]
+fn harder_difficulties_are_more_skilled() {
+    let difficulties = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Impossible,
+    ];
+    for pair in difficulties.windows(2) {
+        let (easier, harder) = (pair[0].skill(), pair[1].skill());
+        assert!(harder.reaction_delay < easier.reaction_delay);
+        assert!(harder.prediction_noise < easier.prediction_noise);
+        assert!(harder.max_speed > easier.max_speed);
+        assert!(harder.miss_chance < easier.miss_chance);
+        assert_eq!(pair[0].next(), pair[1]);
+    }
+    assert_eq!(Difficulty::Impossible.next(), Difficulty::Easy);
+}
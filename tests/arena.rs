@@ -0,0 +1,104 @@
+use std::process::Command;
+
+fn arena(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_pong-arena"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn arena_reports_every_match() {
+    let csv = arena(&[
+        "-n",
+        "2",
+        "-s",
+        "7",
+        "-f",
+        "csv",
+        "computer:impossible",
+        "idle",
+    ]);
+    let rows: Vec<Vec<&str>> = csv.lines().map(|line| line.split(',').collect()).collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0][0], "seed");
+    for (row, seed) in rows[1..].iter().zip(["7", "8"]) {
+        assert_eq!(row[0], seed);
+        assert_eq!(row[1], "left");
+        assert_eq!(row[2], "10");
+    }
+}
+
+#[test]
+fn arena_reports_sets_won() {
+    let path = std::env::temp_dir().join(format!("pong-{}-arena-sets.ron", std::process::id()));
+    std::fs::write(&path, "(victory: BestOfSets(sets: 3, points: 2))").unwrap();
+    let csv = arena(&[
+        "-n",
+        "1",
+        "-r",
+        path.to_str().unwrap(),
+        "-f",
+        "csv",
+        "computer:impossible",
+        "idle",
+    ]);
+    std::fs::remove_file(path).unwrap();
+
+    let rows: Vec<Vec<&str>> = csv.lines().map(|line| line.split(',').collect()).collect();
+    assert_eq!(rows[0][4..6], ["left_sets", "right_sets"]);
+    assert_eq!(rows[1][1], "left");
+    // Points start over with every set, so only the sets tell who won
+    assert_eq!(rows[1][4..6], ["2", "0"]);
+}
+
+#[test]
+fn arena_matches_are_reproducible() {
+    let args = ["-n", "2", "-f", "json", "computer:easy", "idle"];
+    let report: serde_json::Value = serde_json::from_str(&arena(&args)).unwrap();
+    assert_eq!(report["summary"]["matches"], 2);
+    assert_eq!(report["matches"].as_array().unwrap().len(), 2);
+    assert_eq!(arena(&args), arena(&args));
+}
+
+#[test]
+fn arena_rejects_bad_arguments() {
+    let output = Command::new(env!("CARGO_BIN_EXE_pong-arena"))
+        .args(["computer:clueless", "idle"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn arena_plays_in_an_arena_file() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/arenas/pillars.ron");
+    let args = [
+        "-n",
+        "1",
+        "-a",
+        path,
+        "-f",
+        "csv",
+        "computer:impossible",
+        "idle",
+    ];
+    let csv = arena(&args);
+    assert_eq!(csv.lines().count(), 2);
+    assert_ne!(
+        csv,
+        arena(&["-n", "1", "-f", "csv", "computer:impossible", "idle"])
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_pong-arena"))
+        .args(["-a", "does/not/exist.ron", "idle", "idle"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
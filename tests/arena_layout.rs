@@ -0,0 +1,354 @@
+mod common;
+
+use std::f32::consts::FRAC_1_SQRT_2;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy_pong::arena::{Arena, ArenaError, Lane, Obstacle, Segment, WALL_THICKNESS};
+use bevy_pong::ball::{Ball, WallHit, BALL_SIZE};
+use bevy_pong::bindings::KeyBindings;
+use bevy_pong::controller::PaddleControllers;
+use bevy_pong::game::{MatchClock, MatchSetup};
+use bevy_pong::paddle::Side;
+use bevy_pong::physics::{
+    closest_point_on_segment, penetration, sweep_segment, PhysicsSet, Position,
+};
+use bevy_pong::replay::{LastReplay, ReplayPlayback};
+use bevy_pong::rules::{MatchRules, VictoryCondition};
+use bevy_pong::{AppState, WINDOW_WIDTH};
+use common::*;
+
+fn arena_path(file: &str) -> PathBuf {
+    PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/arenas")).join(file)
+}
+
+// Writes out an arena file unique to the calling test
+fn load(name: &str, contents: &str) -> Result<Arena, ArenaError> {
+    let path = std::env::temp_dir().join(format!("pong-{}-arena-{name}.ron", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    let arena = Arena::load(&path);
+    std::fs::remove_file(path).unwrap();
+    arena
+}
+
+fn invalid_field(result: Result<Arena, ArenaError>) -> &'static str {
+    match result {
+        Err(ArenaError::Invalid { field, .. }) => field,
+        _ => panic!("The arena should have been rejected"),
+    }
+}
+
+// Plays in an arena with both paddles dodging the ball
+fn dodging_app(arena: Arena) -> App {
+    let mut app = headless_app_with_setup(MatchSetup {
+        seed: Some(TEST_SEED),
+        arena,
+        ..default()
+    });
+    let mut controllers = app.world.resource_mut::<PaddleControllers>();
+    controllers.set(Side::Left, || Dodger);
+    controllers.set(Side::Right, || Dodger);
+    start_match(&mut app);
+    app
+}
+
+#[derive(Resource, Default)]
+struct BallTrail(Vec<Vec2>);
+
+// Remembers where the balls were after each step, once they have moved
+fn record_balls(mut trail: ResMut<BallTrail>, ball_query: Query<&Position, With<Ball>>) {
+    trail
+        .0
+        .extend(ball_query.iter().map(|position| position.current));
+}
+
+#[derive(Resource, Default)]
+struct WallHits(Vec<Vec2>);
+
+fn record_wall_hits(mut hits: ResMut<WallHits>, mut wall_hit_events: EventReader<WallHit>) {
+    hits.0.extend(wall_hit_events.iter().map(|hit| hit.point));
+}
+
+fn track_balls(app: &mut App) {
+    app.init_resource::<BallTrail>()
+        .init_resource::<WallHits>()
+        .add_systems(
+            FixedUpdate,
+            (record_balls, record_wall_hits).in_set(PhysicsSet::PowerUps),
+        );
+}
+
+#[test]
+fn shipped_arenas_are_valid() {
+    let mut names = Vec::new();
+    for entry in std::fs::read_dir(arena_path("")).unwrap() {
+        let path = entry.unwrap().path();
+        let arena = Arena::load(&path).unwrap_or_else(|error| panic!("{path:?}: {error}"));
+        names.push(arena.name);
+    }
+    assert!(names.len() > 1);
+    assert_eq!(
+        Arena::load(arena_path("classic.ron")).unwrap(),
+        Arena::default()
+    );
+}
+
+#[test]
+fn bad_arenas_are_rejected_by_name() {
+    assert_eq!(
+        invalid_field(load("goal", "(goal_height: Some(10.0))")),
+        "goal_height"
+    );
+    assert_eq!(
+        invalid_field(load("lane", "(left_lane: (inset: 500.0))")),
+        "left_lane"
+    );
+    assert_eq!(
+        invalid_field(load("lane_top", "(right_lane: (top: 400.0))")),
+        "right_lane"
+    );
+    assert_eq!(
+        invalid_field(load(
+            "center",
+            "(walls: [(start: (-50.0, -50.0), end: (50.0, 50.0))])"
+        )),
+        "walls"
+    );
+    assert_eq!(
+        invalid_field(load(
+            "offboard",
+            "(walls: [(start: (0.0, 100.0), end: (900.0, 100.0))])"
+        )),
+        "walls"
+    );
+    assert_eq!(
+        invalid_field(load(
+            "moving",
+            "(obstacles: [(center: (100.0, 0.0), half_size: (10.0, 10.0), motion: Some((offset: (-200.0, 0.0), period: 2.0)))])"
+        )),
+        "obstacles"
+    );
+    assert!(matches!(
+        load("typo", "(wall: [])"),
+        Err(ArenaError::Parse(_))
+    ));
+
+    let error = load("message", "(goal_height: Some(10.0))").unwrap_err();
+    assert!(error.to_string().contains("goal_height"));
+}
+
+#[test]
+fn balls_glance_off_of_angled_segments() {
+    // A wall at 45 degrees turns a ball heading right to head straight up
+    let hit = sweep_segment(
+        Vec2::new(-50.0, 20.0),
+        Vec2::new(100.0, 0.0),
+        Vec2::new(-50.0, -50.0),
+        Vec2::new(50.0, 50.0),
+        10.0,
+    )
+    .unwrap();
+    let expected_normal = Vec2::new(-FRAC_1_SQRT_2, FRAC_1_SQRT_2);
+    assert!((hit.normal - expected_normal).length() < 1e-5);
+    assert!((hit.time - (70.0 - 10.0 * 2.0_f32.sqrt()) / 100.0).abs() < 1e-5);
+    let velocity = Vec2::X;
+    let reflected = velocity - 2.0 * velocity.dot(hit.normal) * hit.normal;
+    assert!((reflected - Vec2::Y).length() < 1e-5);
+
+    // Running into the end of a wall bounces straight back off of it
+    let hit = sweep_segment(
+        Vec2::new(0.0, 100.0),
+        Vec2::new(0.0, -100.0),
+        Vec2::new(0.0, -50.0),
+        Vec2::new(0.0, 50.0),
+        10.0,
+    )
+    .unwrap();
+    assert!((hit.time - 0.4).abs() < 1e-5);
+    assert!((hit.normal - Vec2::Y).length() < 1e-5);
+
+    // Heading away from a wall, or passing it by, is not a hit
+    let wall = (Vec2::new(0.0, -50.0), Vec2::new(0.0, 50.0));
+    assert!(sweep_segment(Vec2::new(20.0, 0.0), Vec2::X * 50.0, wall.0, wall.1, 10.0).is_none());
+    assert!(sweep_segment(
+        Vec2::new(-50.0, 80.0),
+        Vec2::X * 100.0,
+        wall.0,
+        wall.1,
+        10.0
+    )
+    .is_none());
+}
+
+#[test]
+fn angled_walls_keep_the_ball_out() {
+    let arena = Arena::load(arena_path("chevrons.ron")).unwrap();
+    let walls = arena.walls.clone();
+    let mut app = dodging_app(arena);
+    track_balls(&mut app);
+    step(&mut app, 60 * 60);
+
+    // The ball never gets any closer to a wall than it is kept from it
+    let radius = BALL_SIZE / 2.0 + WALL_THICKNESS / 2.0;
+    let trail = &app.world.resource::<BallTrail>().0;
+    assert!(!trail.is_empty());
+    for position in trail {
+        for wall in walls.iter() {
+            let distance =
+                position.distance(closest_point_on_segment(*position, wall.start, wall.end));
+            assert!(distance > radius - 0.1, "{position} is inside of {wall:?}");
+        }
+    }
+
+    // And some of its bounces were off of the walls rather than the borders
+    let hits = &app.world.resource::<WallHits>().0;
+    assert!(hits.iter().any(|point| walls.iter().any(|wall| {
+        point.distance(closest_point_on_segment(*point, wall.start, wall.end)) < radius + 0.1
+    })));
+}
+
+#[test]
+fn balls_only_score_through_the_goal_opening() {
+    let arena = Arena {
+        goal_height: Some(80.0),
+        ..default()
+    };
+    let middle = arena.goal_posts()[0].end.y + 40.0;
+    let mut app = dodging_app(arena);
+    track_balls(&mut app);
+    step(&mut app, 60 * 60);
+
+    // A ball past the goal line is always level with the opening
+    let goal_line = WINDOW_WIDTH / 2.0 - BALL_SIZE / 2.0;
+    let trail = &app.world.resource::<BallTrail>().0;
+    for position in trail.iter().filter(|position| position.x.abs() > goal_line) {
+        assert!(
+            (position.y - middle).abs() < 40.0 + BALL_SIZE,
+            "{position} went through a goal post"
+        );
+    }
+
+    // The posts turned the ball back at least once
+    let hits = &app.world.resource::<WallHits>().0;
+    assert!(hits
+        .iter()
+        .any(|point| point.x.abs() > goal_line - WALL_THICKNESS));
+}
+
+#[test]
+fn obstacles_move_with_the_match_clock() {
+    let arena = Arena::load(arena_path("crossfire.ron")).unwrap();
+    let mut app = dodging_app(arena);
+
+    let ball_half_size = Vec2::splat(BALL_SIZE / 2.0 - 0.1);
+    let mut moved = false;
+    for _ in 0..60 * 20 {
+        app.update();
+        let elapsed = app.world.resource::<MatchClock>().elapsed;
+        let obstacles: Vec<(Vec2, Obstacle)> = app
+            .world
+            .query::<(&Position, &Obstacle)>()
+            .iter(&app.world)
+            .map(|(position, obstacle)| (position.current, *obstacle))
+            .collect();
+        assert_eq!(obstacles.len(), 2);
+        let balls: Vec<Vec2> = app
+            .world
+            .query_filtered::<&Position, With<Ball>>()
+            .iter(&app.world)
+            .map(|position| position.current)
+            .collect();
+
+        for (position, obstacle) in obstacles {
+            assert_eq!(position, obstacle.position_at(elapsed));
+            moved |= position != obstacle.center;
+
+            // However they move, the ball is never left inside of one
+            for ball in balls.iter() {
+                assert!(
+                    penetration(*ball, ball_half_size, position, obstacle.half_size).is_none(),
+                    "{ball} is inside of the obstacle at {position}"
+                );
+            }
+        }
+    }
+    assert!(moved);
+}
+
+#[test]
+fn paddles_stay_in_their_lanes() {
+    let lane = Lane {
+        inset: 60.0,
+        bottom: -180.0,
+        top: 140.0,
+    };
+    let mut app = headless_app_with_setup(MatchSetup {
+        seed: Some(TEST_SEED),
+        arena: Arena {
+            left_lane: lane,
+            ..default()
+        },
+        ..default()
+    });
+    start_match(&mut app);
+    let half_height = MatchRules::default().paddle_height / 2.0;
+    let left_x = -WINDOW_WIDTH / 2.0 + lane.inset;
+    assert_eq!(paddle_position(&mut app, Side::Left).x, left_x);
+
+    let keys = KeyBindings::default().left;
+    press(&mut app, keys.up);
+    step(&mut app, 120);
+    release(&mut app, keys.up);
+    assert_eq!(
+        paddle_position(&mut app, Side::Left),
+        Vec2::new(left_x, lane.top - half_height)
+    );
+
+    press(&mut app, keys.down);
+    step(&mut app, 120);
+    release(&mut app, keys.down);
+    assert_eq!(
+        paddle_position(&mut app, Side::Left),
+        Vec2::new(left_x, lane.bottom + half_height)
+    );
+    // The other paddle is left where it always was
+    assert_eq!(
+        paddle_position(&mut app, Side::Right).x,
+        WINDOW_WIDTH / 2.0 - Lane::default().inset
+    );
+}
+
+#[test]
+fn replays_play_out_in_the_arena_they_were_recorded_in() {
+    let arena = Arena::load(arena_path("crossfire.ron")).unwrap();
+    let mut app = headless_app_with_setup(MatchSetup {
+        seed: Some(TEST_SEED),
+        arena: arena.clone(),
+        ..default()
+    });
+    app.insert_resource(MatchRules {
+        victory: VictoryCondition::FirstTo(3),
+        ..default()
+    });
+    start_match(&mut app);
+    let final_score = play_match(&mut app);
+    app.update();
+    let replay = app.world.resource::<LastReplay>().0.clone();
+    assert_eq!(replay.setup().arena, arena);
+
+    // Watch it back from the menu, which is set up for the classic board
+    let mut app = headless_app(false);
+    app.insert_resource(ReplayPlayback::new(replay));
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Replay);
+    app.update();
+    assert_eq!(app.world.query::<&Segment>().iter(&app.world).count(), 4);
+    while !app.world.resource::<ReplayPlayback>().is_finished() {
+        app.update();
+    }
+    app.update();
+    let replayed_score = score(&mut app).unwrap();
+    assert_eq!(replayed_score.left_score, final_score.left_score);
+    assert_eq!(replayed_score.right_score, final_score.right_score);
+}
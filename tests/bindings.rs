@@ -0,0 +1,91 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_pong::bindings::{Action, Binding, BindingsError, KeyBindings};
+use bevy_pong::paddle::Side;
+use bevy_pong::pause::PauseState;
+use common::*;
+
+const LEFT_UP: Binding = Binding {
+    side: Side::Left,
+    action: Action::Up,
+};
+const RIGHT_PAUSE: Binding = Binding {
+    side: Side::Right,
+    action: Action::Pause,
+};
+
+#[test]
+fn keys_already_in_use_are_refused() {
+    let mut bindings = KeyBindings::default();
+    assert_eq!(
+        bindings.rebind(LEFT_UP, KeyCode::Down),
+        Err(Binding {
+            side: Side::Right,
+            action: Action::Down,
+        })
+    );
+    assert_eq!(bindings.left.up, KeyCode::W);
+
+    // An action can always be given its own key back
+    assert_eq!(bindings.rebind(LEFT_UP, KeyCode::W), Ok(()));
+    assert_eq!(bindings.rebind(LEFT_UP, KeyCode::Q), Ok(()));
+    assert_eq!(bindings.left.up, KeyCode::Q);
+    assert_eq!(bindings.conflict(), None);
+}
+
+#[test]
+fn bindings_survive_a_round_trip_to_disk() {
+    let path = std::env::temp_dir().join(format!("pong-controls-{}.ron", std::process::id()));
+    let mut bindings = KeyBindings::default();
+    bindings.rebind(RIGHT_PAUSE, KeyCode::Back).unwrap();
+    bindings.save(&path).unwrap();
+    let loaded = KeyBindings::load(&path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(loaded, bindings);
+}
+
+#[test]
+fn conflicting_files_are_refused() {
+    let path = std::env::temp_dir().join(format!("pong-conflict-{}.ron", std::process::id()));
+    let mut bindings = KeyBindings::default();
+    bindings.right.pause = KeyCode::W;
+    bindings.save(&path).unwrap();
+    let loaded = KeyBindings::load(&path);
+    std::fs::remove_file(path).unwrap();
+    assert!(matches!(loaded, Err(BindingsError::Conflict(..))));
+}
+
+#[test]
+fn matches_use_the_rebound_keys() {
+    let mut app = headless_app(false);
+    let mut bindings = KeyBindings::default();
+    bindings.rebind(LEFT_UP, KeyCode::Q).unwrap();
+    bindings.rebind(RIGHT_PAUSE, KeyCode::Return).unwrap();
+    app.insert_resource(bindings);
+    start_match(&mut app);
+
+    // The old key does nothing, while the new one moves the paddle
+    let start = paddle_position(&mut app, Side::Left);
+    press(&mut app, KeyCode::W);
+    step(&mut app, 10);
+    assert_eq!(paddle_position(&mut app, Side::Left), start);
+    release(&mut app, KeyCode::W);
+    press(&mut app, KeyCode::Q);
+    step(&mut app, 10);
+    assert!(paddle_position(&mut app, Side::Left).y > start.y);
+    release(&mut app, KeyCode::Q);
+
+    tap(&mut app, KeyCode::P);
+    app.update();
+    assert_eq!(
+        *app.world.resource::<State<PauseState>>().get(),
+        PauseState::Running
+    );
+    tap(&mut app, KeyCode::Return);
+    app.update();
+    assert_eq!(
+        *app.world.resource::<State<PauseState>>().get(),
+        PauseState::Paused
+    );
+}
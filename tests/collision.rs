@@ -0,0 +1,89 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_pong::ball::{Ball, PaddleHit, BALL_SIZE};
+use bevy_pong::paddle::{Side, PADDLE_WIDTH};
+use bevy_pong::physics::{penetration, PhysicsSet, Position};
+use bevy_pong::rules::MatchRules;
+use common::*;
+
+// Steps left for the left paddle to be pushed a little way into the back
+// of the ball
+#[derive(Resource, Default)]
+struct Shove(u32);
+
+fn shove_ball(
+    mut shove: ResMut<Shove>,
+    ball_query: Query<&Position, With<Ball>>,
+    mut paddle_query: Query<(&mut Position, &Side), Without<Ball>>,
+) {
+    if shove.0 == 0 {
+        return;
+    }
+    shove.0 -= 1;
+    let ball = ball_query.single().current;
+    let touching = (PADDLE_WIDTH + BALL_SIZE) / 2.0;
+    for (mut position, side) in paddle_query.iter_mut() {
+        if *side == Side::Left {
+            position.teleport(Vec2::new(ball.x - touching + 3.0, ball.y));
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct Hits(u32);
+
+fn count_hits(mut hits: ResMut<Hits>, mut paddle_hits: EventReader<PaddleHit>) {
+    hits.0 += paddle_hits.iter().count() as u32;
+}
+
+#[test]
+fn a_paddle_pushing_into_the_ball_hits_it_once() {
+    let mut app = headless_app(false);
+    // Slow enough for the ball to still be touching the paddle after a step
+    app.insert_resource(MatchRules {
+        ball_speed: 60.0,
+        ball_speed_increase: 0.0,
+        ..default()
+    });
+    app.init_resource::<Shove>()
+        .init_resource::<Hits>()
+        .add_systems(
+            FixedUpdate,
+            (
+                shove_ball
+                    .after(PhysicsSet::Paddles)
+                    .before(PhysicsSet::Ball),
+                count_hits.in_set(PhysicsSet::Victory),
+            ),
+        );
+    start_match(&mut app);
+    wait_for_serve(&mut app);
+    app.world.resource_mut::<Hits>().0 = 0;
+
+    // Several steps in a row with the paddle pushed into the ball, which is
+    // pushed back out each time but only hit the first time
+    app.world.resource_mut::<Shove>().0 = 8;
+    for _ in 0..4 {
+        app.update();
+        let ball = app
+            .world
+            .query_filtered::<&Position, With<Ball>>()
+            .single(&app.world)
+            .current;
+        let paddle = paddle_position(&mut app, Side::Left);
+        let height = app.world.resource::<MatchRules>().paddle_height;
+        let overlap = penetration(
+            ball,
+            Vec2::splat(BALL_SIZE / 2.0),
+            paddle,
+            Vec2::new(PADDLE_WIDTH, height) / 2.0,
+        );
+        assert!(overlap.is_none(), "{ball} {paddle}");
+    }
+    step(&mut app, 2);
+
+    assert_eq!(app.world.resource::<Hits>().0, 1);
+    let velocity = app.world.query::<&Ball>().single(&app.world).velocity();
+    assert!(velocity.is_finite() && velocity != Vec2::ZERO, "{velocity}");
+}
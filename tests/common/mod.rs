@@ -0,0 +1,147 @@
+// Shared harness for driving headless matches from the integration tests
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_pong::ball::Ball;
+use bevy_pong::controller::{GameSnapshot, PaddleController};
+use bevy_pong::game::MatchSetup;
+use bevy_pong::paddle::Side;
+use bevy_pong::physics::Position;
+use bevy_pong::score::{PointScored, Score};
+use bevy_pong::{AppState, PongCorePlugin};
+
+// Every call to `App::update` advances the clock by exactly one 60hz frame
+pub const FRAME_TIME: Duration = Duration::from_nanos(16_666_667);
+// Seed used by every test match, so that failures can be reproduced
+pub const TEST_SEED: u64 = 0x5EED;
+// Plenty of time for a full match to be played out
+pub const MAX_MATCH_FRAMES: usize = 60 * 60 * 20;
+
+/// Builds an app that runs the game rules without a window, sitting on the menu
+pub fn headless_app(is_single_player: bool) -> App {
+    headless_app_with_setup(MatchSetup {
+        is_single_player,
+        seed: Some(TEST_SEED),
+        ..default()
+    })
+}
+
+pub fn headless_app_with_setup(match_setup: MatchSetup) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, PongCorePlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME))
+        .insert_resource(match_setup);
+    // Let the clock start ticking before anything else happens
+    app.update();
+    app
+}
+
+/// Leaves the menu and lets the match get set up
+pub fn start_match(app: &mut App) {
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Game);
+    app.update();
+}
+
+/// Steps the app until the ball has been served
+pub fn wait_for_serve(app: &mut App) {
+    for _ in 0..MAX_MATCH_FRAMES {
+        if !app.world.query::<&Ball>().single(&app.world).is_held() {
+            return;
+        }
+        app.update();
+    }
+    panic!("The ball was never served");
+}
+
+pub fn step(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+pub fn state(app: &App) -> AppState {
+    *app.world.resource::<State<AppState>>().get()
+}
+
+pub fn press(app: &mut App, key: KeyCode) {
+    app.world.resource_mut::<Input<KeyCode>>().press(key);
+}
+
+pub fn release(app: &mut App, key: KeyCode) {
+    app.world.resource_mut::<Input<KeyCode>>().release(key);
+}
+
+/// Presses and releases a key over a single frame. There is no input plugin
+/// without a window, so the key has to be cleared out by hand.
+pub fn tap(app: &mut App, key: KeyCode) {
+    press(app, key);
+    app.update();
+    let mut keyboard_input = app.world.resource_mut::<Input<KeyCode>>();
+    keyboard_input.release(key);
+    keyboard_input.clear();
+}
+
+pub fn score(app: &mut App) -> Option<Score> {
+    app.world
+        .query::<&Score>()
+        .get_single(&app.world)
+        .ok()
+        .copied()
+}
+
+pub fn paddle_position(app: &mut App, side: Side) -> Vec2 {
+    app.world
+        .query::<(&Position, &Side)>()
+        .iter(&app.world)
+        .find(|(_, paddle_side)| **paddle_side == side)
+        .map(|(position, _)| position.current)
+        .expect("Both paddles should exist during a match")
+}
+
+/// Steps the app until the match is over, returning the final score
+pub fn play_match(app: &mut App) -> Score {
+    let mut last_score = score(app).expect("A match should be in progress");
+    for _ in 0..MAX_MATCH_FRAMES {
+        app.update();
+        if state(app) != AppState::Game {
+            return last_score;
+        }
+        last_score = score(app).expect("The score should exist until the match ends");
+    }
+    panic!("The match did not finish in time");
+}
+
+/// Keeps its paddle level with the ball it is shown
+pub struct Follower;
+
+impl PaddleController for Follower {
+    fn movement(&mut self, snapshot: &GameSnapshot) -> f32 {
+        (snapshot.ball.position.y - snapshot.paddle.y) / 10.0
+    }
+}
+
+/// Gets out of the way of the ball
+pub struct Dodger;
+
+impl PaddleController for Dodger {
+    fn movement(&mut self, snapshot: &GameSnapshot) -> f32 {
+        if snapshot.ball.position.y > snapshot.paddle.y {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Every point scored so far, filled in by `record_points`
+#[derive(Resource, Default)]
+pub struct Points(pub Vec<PointScored>);
+
+pub fn record_points(mut points: ResMut<Points>, mut point_events: EventReader<PointScored>) {
+    points.0.extend(point_events.iter().copied());
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use bevy_pong::controller::{GameSnapshot, PaddleController, PaddleControllers};
+use bevy_pong::paddle::Side;
+use bevy_pong::replay::{LastReplay, ReplayPlayback};
+use bevy_pong::rules::MatchRules;
+use bevy_pong::AppState;
+use common::*;
+
+// Moves up at full speed, noting down what it was shown
+struct Climber(Arc<Mutex<Vec<(Side, bool)>>>);
+
+impl PaddleController for Climber {
+    fn movement(&mut self, snapshot: &GameSnapshot) -> f32 {
+        self.0
+            .lock()
+            .unwrap()
+            .push((snapshot.side, snapshot.opponent.is_some()));
+        5.0
+    }
+}
+
+fn set_controller<C: PaddleController>(
+    app: &mut App,
+    side: Side,
+    factory: impl Fn() -> C + Send + Sync + 'static,
+) {
+    app.world
+        .resource_mut::<PaddleControllers>()
+        .set(side, factory);
+}
+
+#[test]
+fn controllers_move_their_own_side() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let mut app = headless_app(false);
+    let climber_seen = seen.clone();
+    set_controller(&mut app, Side::Right, move || Climber(climber_seen.clone()));
+    start_match(&mut app);
+
+    let left_start = paddle_position(&mut app, Side::Left);
+    let right_start = paddle_position(&mut app, Side::Right);
+    step(&mut app, 10);
+    assert!(paddle_position(&mut app, Side::Right).y > right_start.y);
+    assert_eq!(paddle_position(&mut app, Side::Left), left_start);
+
+    let seen = seen.lock().unwrap();
+    assert!(!seen.is_empty());
+    assert!(seen.iter().all(|&seen| seen == (Side::Right, true)));
+}
+
+#[test]
+fn controllers_can_play_each_other() {
+    let mut app = headless_app(false);
+    // Following the ball only keeps up with it while it doesn't speed up, and
+    // while it can't climb faster than the paddle. Serves never leave at more
+    // than 45 degrees, but a ball returned off the corner of the idle right
+    // paddle leaves at up to 70 or so, so the ball is kept to the paddle's
+    // speed. Which returns come off a corner depends on where the rallies
+    // start, which moved once the ball was held for a countdown.
+    let rules = MatchRules::default();
+    app.insert_resource(MatchRules {
+        ball_speed: rules.paddle_speed,
+        ball_speed_increase: 0.0,
+        ..rules
+    });
+    set_controller(&mut app, Side::Left, || Follower);
+    start_match(&mut app);
+
+    let final_score = play_match(&mut app);
+    assert_eq!(state(&app), AppState::End);
+    assert_eq!(final_score.right_score, 0);
+}
+
+#[test]
+fn every_match_gets_a_fresh_controller() {
+    let built = Arc::new(AtomicUsize::new(0));
+    let mut app = headless_app(false);
+    let counter = built.clone();
+    set_controller(&mut app, Side::Left, move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Follower
+    });
+
+    start_match(&mut app);
+    play_match(&mut app);
+    app.update();
+    assert_eq!(built.load(Ordering::SeqCst), 1);
+
+    // Watching the match back uses the recorded inputs instead
+    let replay = app.world.resource::<LastReplay>().0.clone();
+    app.insert_resource(ReplayPlayback::new(replay));
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Replay);
+    step(&mut app, 10);
+    assert_eq!(built.load(Ordering::SeqCst), 1);
+
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Menu);
+    app.update();
+    start_match(&mut app);
+    assert_eq!(built.load(Ordering::SeqCst), 2);
+
+    // Once cleared, the side goes back to the keyboard
+    app.world
+        .resource_mut::<PaddleControllers>()
+        .clear(Side::Left);
+    assert!(!app.world.resource::<PaddleControllers>().is_set(Side::Left));
+}
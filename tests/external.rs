@@ -0,0 +1,176 @@
+mod common;
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy_pong::controller::PaddleControllers;
+use bevy_pong::external::{BotAddress, ExternalBot};
+use bevy_pong::paddle::Side;
+use bevy_pong::physics::PHYSICS_TIMESTEP;
+use bevy_pong::rules::MatchRules;
+use common::*;
+
+fn play_against(address: BotAddress, timeout: Duration) -> (Vec2, Vec2) {
+    let mut app = headless_app(false);
+    app.world
+        .resource_mut::<PaddleControllers>()
+        .set(Side::Right, move || {
+            ExternalBot::new(address.clone()).with_timeout(timeout)
+        });
+    start_match(&mut app);
+
+    let start = paddle_position(&mut app, Side::Right);
+    step(&mut app, 20);
+    (start, paddle_position(&mut app, Side::Right))
+}
+
+fn shell_bot(script: &str) -> BotAddress {
+    BotAddress::Command(vec!["sh".into(), "-c".into(), script.into()])
+}
+
+#[test]
+fn addresses_are_parsed_by_their_prefix() {
+    assert_eq!(
+        "tcp:127.0.0.1:4000".parse(),
+        Ok(BotAddress::Tcp("127.0.0.1:4000".into()))
+    );
+    assert_eq!(
+        "unix:/tmp/bot.sock".parse(),
+        Ok(BotAddress::Unix("/tmp/bot.sock".into()))
+    );
+    assert_eq!(
+        "python3 bot.py --fast".parse(),
+        Ok(BotAddress::Command(vec![
+            "python3".into(),
+            "bot.py".into(),
+            "--fast".into()
+        ]))
+    );
+    assert!("  ".parse::<BotAddress>().is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn child_process_bots_move_their_paddle() {
+    let (start, end) = play_against(
+        shell_bot("while read line; do echo up; done"),
+        Duration::from_secs(1),
+    );
+    assert!(end.y > start.y);
+}
+
+#[cfg(unix)]
+#[test]
+fn slow_bots_stay_put() {
+    let (start, end) = play_against(
+        shell_bot("while read line; do sleep 1; echo up; done"),
+        Duration::from_millis(1),
+    );
+    assert_eq!(end, start);
+}
+
+#[cfg(unix)]
+#[test]
+fn silent_bots_never_hold_up_the_match() {
+    let mut app = headless_app(false);
+    let mut controllers = app.world.resource_mut::<PaddleControllers>();
+    for side in [Side::Left, Side::Right] {
+        controllers.set(side, || {
+            ExternalBot::new(shell_bot("while read line; do :; done"))
+        });
+    }
+    start_match(&mut app);
+
+    // Every frame is two steps, which have to take less time than they stand for
+    let frames = 60;
+    let start = Instant::now();
+    step(&mut app, frames);
+    let steps = frames as f32 * FRAME_TIME.as_secs_f32() / PHYSICS_TIMESTEP;
+    assert!(start.elapsed().as_secs_f32() / steps < PHYSICS_TIMESTEP);
+}
+
+#[cfg(unix)]
+#[test]
+fn bots_that_stop_reading_never_hold_up_the_match() {
+    let mut app = headless_app(false);
+    app.world
+        .resource_mut::<PaddleControllers>()
+        .set(Side::Right, || {
+            ExternalBot::new(shell_bot("exec sleep 1000"))
+        });
+    start_match(&mut app);
+
+    // Far more steps than the bot's input can hold messages for
+    let frames = 600;
+    let start = Instant::now();
+    step(&mut app, frames);
+    let steps = frames as f32 * FRAME_TIME.as_secs_f32() / PHYSICS_TIMESTEP;
+    assert!(start.elapsed().as_secs_f32() / steps < PHYSICS_TIMESTEP);
+}
+
+#[cfg(unix)]
+#[test]
+fn bots_that_go_quiet_stay_put() {
+    let mut app = headless_app(false);
+    app.world
+        .resource_mut::<PaddleControllers>()
+        .set(Side::Right, || {
+            ExternalBot::new(shell_bot("read line; echo up; while read line; do :; done"))
+                .with_timeout(Duration::from_millis(200))
+        });
+    start_match(&mut app);
+
+    // Only the step that was answered moves the paddle away from where the
+    // idle one on the left stands
+    step(&mut app, 5);
+    let moved = paddle_position(&mut app, Side::Right).y - paddle_position(&mut app, Side::Left).y;
+    let paddle_speed = app.world.resource::<MatchRules>().paddle_speed;
+    assert!(moved > 0.0);
+    assert!(moved <= paddle_speed * PHYSICS_TIMESTEP + 0.01, "{moved}");
+}
+
+#[test]
+fn unreachable_bots_stay_put() {
+    let bot = ExternalBot::new(BotAddress::Command(vec!["does-not-exist-pong-bot".into()]));
+    assert!(!bot.is_connected());
+
+    let (start, end) = play_against(
+        BotAddress::Command(vec!["does-not-exist-pong-bot".into()]),
+        Duration::from_millis(1),
+    );
+    assert_eq!(end, start);
+}
+
+#[test]
+fn socket_bots_are_sent_the_match() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let bot = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        let mut first_message = None;
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            first_message.get_or_insert(line);
+            if writer.write_all(b"down\n").is_err() {
+                break;
+            }
+        }
+        first_message.unwrap()
+    });
+
+    let (start, end) = play_against(BotAddress::Tcp(address), Duration::from_secs(1));
+    assert!(end.y < start.y);
+
+    let message: serde_json::Value = serde_json::from_str(&bot.join().unwrap()).unwrap();
+    assert_eq!(message["tick"], 0);
+    assert_eq!(message["side"], "right");
+    assert_eq!(message["score"]["left"], 0);
+    assert!(message["ball"]["velocity"][0].is_number());
+    assert!(message["opponent"].is_array());
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use bevy::prelude::*;
+use bevy_pong::gamepad::GamepadAssignments;
+use bevy_pong::paddle::Side;
+use common::*;
+
+/// Presses and releases a gamepad button over a single frame
+fn tap_button(app: &mut App, gamepad: Gamepad, button_type: GamepadButtonType) {
+    let button = GamepadButton::new(gamepad, button_type);
+    app.world
+        .resource_mut::<Input<GamepadButton>>()
+        .press(button);
+    app.update();
+    let mut buttons = app.world.resource_mut::<Input<GamepadButton>>();
+    buttons.release(button);
+    buttons.clear();
+}
+
+fn set_stick(app: &mut App, gamepad: Gamepad, value: f32) {
+    app.world.resource_mut::<Axis<GamepadAxis>>().set(
+        GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY),
+        value,
+    );
+}
+
+fn assignments(app: &App) -> (Option<Gamepad>, Option<Gamepad>) {
+    let assignments = app.world.resource::<GamepadAssignments>();
+    (assignments.left, assignments.right)
+}
+
+#[test]
+fn gamepads_join_on_the_first_free_side() {
+    let mut app = headless_app(false);
+    let first = Gamepad::new(3);
+    let second = Gamepad::new(7);
+
+    tap_button(&mut app, first, GamepadButtonType::South);
+    assert_eq!(assignments(&app), (None, Some(first)));
+
+    // Pressing again doesn't move a gamepad that has already joined
+    tap_button(&mut app, first, GamepadButtonType::East);
+    assert_eq!(assignments(&app), (None, Some(first)));
+
+    tap_button(&mut app, second, GamepadButtonType::Start);
+    assert_eq!(assignments(&app), (Some(second), Some(first)));
+
+    // Nobody else fits once both sides are taken
+    tap_button(&mut app, Gamepad::new(9), GamepadButtonType::South);
+    assert_eq!(assignments(&app), (Some(second), Some(first)));
+}
+
+#[test]
+fn the_stick_moves_the_paddle_in_proportion() {
+    let distance_moved = |value: f32| {
+        let mut app = headless_app(false);
+        let gamepad = Gamepad::new(0);
+        tap_button(&mut app, gamepad, GamepadButtonType::South);
+        start_match(&mut app);
+
+        let start = paddle_position(&mut app, Side::Right);
+        set_stick(&mut app, gamepad, value);
+        step(&mut app, 10);
+        paddle_position(&mut app, Side::Right).y - start.y
+    };
+
+    let full = distance_moved(1.0);
+    let half = distance_moved(0.5);
+    let down = distance_moved(-0.5);
+    assert!(full > 0.0);
+    assert!((half - full / 2.0).abs() < 1.0, "{half} vs {full}");
+    assert!((down + half).abs() < 1.0, "{down} vs {half}");
+}
+
+#[test]
+fn the_dpad_moves_the_paddle_at_full_speed() {
+    let mut app = headless_app(false);
+    let gamepad = Gamepad::new(0);
+    tap_button(&mut app, gamepad, GamepadButtonType::South);
+    tap_button(&mut app, Gamepad::new(1), GamepadButtonType::South);
+    start_match(&mut app);
+
+    // Matches a player holding the keys on the other side
+    let start = paddle_position(&mut app, Side::Right);
+    let other_start = paddle_position(&mut app, Side::Left);
+    app.world
+        .resource_mut::<Input<GamepadButton>>()
+        .press(GamepadButton::new(gamepad, GamepadButtonType::DPadDown));
+    press(&mut app, KeyCode::S);
+    step(&mut app, 10);
+
+    let moved = paddle_position(&mut app, Side::Right).y - start.y;
+    let other_moved = paddle_position(&mut app, Side::Left).y - other_start.y;
+    assert!(moved < 0.0);
+    assert_eq!(moved, other_moved);
+}
+
+#[test]
+fn unplugging_a_gamepad_frees_its_side() {
+    let mut app = headless_app(false);
+    let first = Gamepad::new(0);
+    let second = Gamepad::new(1);
+    tap_button(&mut app, first, GamepadButtonType::South);
+    tap_button(&mut app, second, GamepadButtonType::South);
+
+    app.world.send_event(GamepadConnectionEvent {
+        gamepad: first,
+        connection: GamepadConnection::Disconnected,
+    });
+    app.update();
+    assert_eq!(assignments(&app), (Some(second), None));
+
+    // Plugging it back in and pressing a button lets it join again
+    tap_button(&mut app, first, GamepadButtonType::South);
+    assert_eq!(assignments(&app), (Some(second), Some(first)));
+}
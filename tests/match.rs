@@ -0,0 +1,103 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_pong::ball::Ball;
+use bevy_pong::game::GameData;
+use bevy_pong::paddle::{Paddle, Side};
+use bevy_pong::physics::Position;
+use bevy_pong::{AppState, UI_HEIGHT, WINDOW_HEIGHT};
+use common::*;
+
+#[test]
+fn match_runs_from_menu_to_end() {
+    let mut app = headless_app(false);
+    assert_eq!(state(&app), AppState::Menu);
+
+    start_match(&mut app);
+    assert_eq!(state(&app), AppState::Game);
+    let score = score(&mut app).unwrap();
+    assert_eq!((score.left_score, score.right_score), (0, 0));
+
+    let final_score = play_match(&mut app);
+    assert_eq!(state(&app), AppState::End);
+    assert_eq!(final_score.left_score.max(final_score.right_score), 10);
+    assert_eq!(
+        app.world.resource::<GameData>().is_left_win,
+        final_score.left_score > final_score.right_score
+    );
+}
+
+#[test]
+fn single_player_match_reaches_a_winner() {
+    let mut app = headless_app(true);
+    start_match(&mut app);
+
+    let final_score = play_match(&mut app);
+    assert_eq!(state(&app), AppState::End);
+    assert_ne!(final_score.left_score, final_score.right_score);
+}
+
+#[test]
+fn match_entities_are_removed_after_the_match() {
+    let mut app = headless_app(false);
+    start_match(&mut app);
+    play_match(&mut app);
+    app.update();
+
+    assert_eq!(app.world.query::<&Ball>().iter(&app.world).count(), 0);
+    assert_eq!(app.world.query::<&Paddle>().iter(&app.world).count(), 0);
+    assert!(score(&mut app).is_none());
+}
+
+#[test]
+fn paddles_follow_keyboard_input() {
+    let mut app = headless_app(false);
+    start_match(&mut app);
+    let left_start = paddle_position(&mut app, Side::Left);
+    let right_start = paddle_position(&mut app, Side::Right);
+
+    press(&mut app, KeyCode::W);
+    press(&mut app, KeyCode::Down);
+    step(&mut app, 10);
+    assert!(paddle_position(&mut app, Side::Left).y > left_start.y);
+    assert!(paddle_position(&mut app, Side::Right).y < right_start.y);
+
+    release(&mut app, KeyCode::W);
+    release(&mut app, KeyCode::Down);
+    let left_stopped = paddle_position(&mut app, Side::Left);
+    step(&mut app, 10);
+    assert_eq!(paddle_position(&mut app, Side::Left), left_stopped);
+}
+
+#[test]
+fn paddles_stay_below_the_scorebar() {
+    let mut app = headless_app(false);
+    start_match(&mut app);
+
+    press(&mut app, KeyCode::Up);
+    step(&mut app, 120);
+    let paddle = paddle_position(&mut app, Side::Right);
+    assert!(paddle.y < WINDOW_HEIGHT / 2.0 - UI_HEIGHT);
+}
+
+#[test]
+fn ball_is_served_from_the_center_after_a_point() {
+    let mut app = headless_app(false);
+    start_match(&mut app);
+
+    for _ in 0..MAX_MATCH_FRAMES {
+        app.update();
+        let score = score(&mut app).unwrap();
+        if score.left_score + score.right_score > 0 {
+            break;
+        }
+    }
+    let score = score(&mut app).unwrap();
+    assert_eq!(score.left_score + score.right_score, 1);
+
+    let ball = app
+        .world
+        .query_filtered::<&Position, With<Ball>>()
+        .single(&app.world);
+    assert!(ball.current.length() < 50.0);
+}
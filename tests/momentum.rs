@@ -0,0 +1,144 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_pong::ball::PaddleHit;
+use bevy_pong::controller::PaddleControllers;
+use bevy_pong::paddle::{PaddleVelocity, Side};
+use bevy_pong::physics::{PhysicsSet, Position};
+use bevy_pong::rules::{MatchRules, PaddleMomentum};
+use common::*;
+
+const MOMENTUM: PaddleMomentum = PaddleMomentum {
+    acceleration: 2000.0,
+    friction: 1000.0,
+};
+
+// Every hit, along with how far the ball was from the paddle's center and
+// how fast the paddle was moving at the time
+#[derive(Resource, Default)]
+struct Hits(Vec<(PaddleHit, Vec2, f32)>);
+
+fn record_hits(
+    mut hits: ResMut<Hits>,
+    mut paddle_hits: EventReader<PaddleHit>,
+    paddle_query: Query<(&Position, &PaddleVelocity)>,
+) {
+    for hit in paddle_hits.iter() {
+        let (position, velocity) = paddle_query.get(hit.paddle).unwrap();
+        hits.0
+            .push((*hit, hit.point - position.current, velocity.0));
+    }
+}
+
+fn velocity(app: &mut App, side: Side) -> f32 {
+    app.world
+        .query::<(&PaddleVelocity, &Side)>()
+        .iter(&app.world)
+        .find(|(_, paddle_side)| **paddle_side == side)
+        .map(|(velocity, _)| velocity.0)
+        .unwrap()
+}
+
+fn app_with_rules(rules: MatchRules) -> App {
+    let mut app = headless_app(false);
+    app.insert_resource(rules);
+    app
+}
+
+#[test]
+fn paddles_move_at_full_speed_at_once_without_momentum() {
+    let mut app = headless_app(false);
+    start_match(&mut app);
+    press(&mut app, KeyCode::Up);
+    app.update();
+    assert!((velocity(&mut app, Side::Right) - 500.0).abs() < 0.01);
+    release(&mut app, KeyCode::Up);
+    app.update();
+    assert_eq!(velocity(&mut app, Side::Right), 0.0);
+}
+
+#[test]
+fn paddles_with_momentum_speed_up_and_coast_to_a_stop() {
+    let mut app = app_with_rules(MatchRules {
+        paddle_momentum: Some(MOMENTUM),
+        ..default()
+    });
+    start_match(&mut app);
+
+    // Speeding up to 500 at 2000 a second takes a quarter of a second
+    press(&mut app, KeyCode::Down);
+    app.update();
+    let first = velocity(&mut app, Side::Right);
+    assert!(first < 0.0 && first > -100.0, "{first}");
+    step(&mut app, 15);
+    let top_speed = velocity(&mut app, Side::Right);
+    assert!((top_speed + 500.0).abs() < 0.01, "{top_speed}");
+
+    // And slowing back down at 1000 a second takes half a second, while the
+    // paddle keeps sliding along
+    release(&mut app, KeyCode::Down);
+    app.update();
+    let coasting = velocity(&mut app, Side::Right);
+    assert!(coasting < 0.0 && coasting > -500.0, "{coasting}");
+    let position = paddle_position(&mut app, Side::Right);
+    step(&mut app, 15);
+    assert!(paddle_position(&mut app, Side::Right).y < position.y);
+    step(&mut app, 30);
+    assert_eq!(velocity(&mut app, Side::Right), 0.0);
+}
+
+#[test]
+fn paddles_stop_dead_at_the_border() {
+    let mut app = app_with_rules(MatchRules {
+        paddle_momentum: Some(MOMENTUM),
+        ..default()
+    });
+    start_match(&mut app);
+    press(&mut app, KeyCode::Up);
+    step(&mut app, 120);
+    assert_eq!(velocity(&mut app, Side::Right), 0.0);
+
+    // So there is no speed left over to fight when heading back
+    release(&mut app, KeyCode::Up);
+    press(&mut app, KeyCode::Down);
+    let position = paddle_position(&mut app, Side::Right);
+    app.update();
+    assert!(paddle_position(&mut app, Side::Right).y < position.y);
+}
+
+#[test]
+fn moving_paddles_put_spin_on_the_ball() {
+    let rules = MatchRules {
+        paddle_spin: 0.5,
+        ..default()
+    };
+    let mut app = app_with_rules(rules);
+    app.init_resource::<Hits>()
+        .add_systems(FixedUpdate, record_hits.in_set(PhysicsSet::Victory));
+    app.world
+        .resource_mut::<PaddleControllers>()
+        .set(Side::Right, || Follower);
+    start_match(&mut app);
+    step(&mut app, 60 * 10);
+
+    let hits = &app.world.resource::<Hits>().0;
+    let spun: Vec<_> = hits
+        .iter()
+        .filter(|(_, _, velocity)| velocity.abs() > 1.0)
+        .collect();
+    assert!(
+        !spun.is_empty(),
+        "The paddle should have hit the ball on the move"
+    );
+    for (hit, offset, velocity) in spun {
+        // The ball leaves further along the way the paddle was moving than it
+        // would have off of a still paddle
+        let still = offset.normalize();
+        let spun = Vec2::from_angle(hit.angle);
+        assert_eq!(
+            (spun.y - still.y).signum(),
+            velocity.signum(),
+            "{offset} {velocity}"
+        );
+    }
+}
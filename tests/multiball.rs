@@ -0,0 +1,173 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_pong::ai::most_threatening;
+use bevy_pong::ball::{Ball, ExtraBall};
+use bevy_pong::controller::{BallState, PaddleControllers};
+use bevy_pong::paddle::Side;
+use bevy_pong::physics::PhysicsSet;
+use bevy_pong::rules::{MatchRules, Multiball};
+use bevy_pong::AppState;
+use common::*;
+
+fn app_with_multiball(is_single_player: bool, multiball: Multiball) -> App {
+    let mut app = headless_app(is_single_player);
+    app.insert_resource(MatchRules {
+        multiball: Some(multiball),
+        ..default()
+    });
+    app
+}
+
+fn ball_count(app: &mut App) -> usize {
+    app.world.query::<&Ball>().iter(&app.world).count()
+}
+
+#[test]
+fn there_is_only_one_ball_without_multiball() {
+    let mut app = headless_app(false);
+    start_match(&mut app);
+    for _ in 0..60 * 30 {
+        app.update();
+        assert_eq!(ball_count(&mut app), 1);
+    }
+}
+
+#[test]
+fn extra_balls_join_on_a_timer_up_to_the_limit() {
+    let mut app = app_with_multiball(
+        false,
+        Multiball {
+            interval: 2.0,
+            rally_hits: 0,
+            max_balls: 3,
+        },
+    );
+    start_match(&mut app);
+
+    step(&mut app, 60 * 2 - 5);
+    assert_eq!(ball_count(&mut app), 1);
+    step(&mut app, 10);
+    assert_eq!(ball_count(&mut app), 2);
+
+    // Extra balls are sent off at once, without waiting for a serve
+    let extra = app
+        .world
+        .query_filtered::<&Ball, With<ExtraBall>>()
+        .single(&app.world);
+    assert!(!extra.is_held());
+    assert_ne!(extra.velocity(), Vec2::ZERO);
+
+    let mut most_balls = 0;
+    for _ in 0..60 * 20 {
+        app.update();
+        most_balls = most_balls.max(ball_count(&mut app));
+    }
+    assert_eq!(most_balls, 3);
+}
+
+#[test]
+fn long_rallies_add_a_ball() {
+    let mut app = app_with_multiball(
+        false,
+        Multiball {
+            interval: 0.0,
+            rally_hits: 3,
+            max_balls: 2,
+        },
+    );
+    // Slow enough for the followers to keep a rally going
+    app.world.resource_mut::<MatchRules>().ball_speed = 500.0;
+    let mut controllers = app.world.resource_mut::<PaddleControllers>();
+    controllers.set(Side::Left, || Follower);
+    controllers.set(Side::Right, || Follower);
+    start_match(&mut app);
+
+    while ball_count(&mut app) == 1 {
+        app.update();
+        assert_eq!(state(&app), AppState::Game);
+    }
+    let served = app
+        .world
+        .query_filtered::<&Ball, Without<ExtraBall>>()
+        .single(&app.world);
+    assert_eq!(served.rally_hits(), 3);
+}
+
+#[test]
+fn long_rallies_add_a_ball_on_frames_shorter_than_a_step() {
+    let mut app = app_with_multiball(
+        false,
+        Multiball {
+            interval: 0.0,
+            rally_hits: 3,
+            max_balls: 2,
+        },
+    );
+    app.world.resource_mut::<MatchRules>().ball_speed = 500.0;
+    // Most frames go by without a step of the game, as on a fast display
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME / 5));
+    let mut controllers = app.world.resource_mut::<PaddleControllers>();
+    controllers.set(Side::Left, || Follower);
+    controllers.set(Side::Right, || Follower);
+    start_match(&mut app);
+
+    let served_hits = |app: &mut App| {
+        app.world
+            .query_filtered::<&Ball, Without<ExtraBall>>()
+            .single(&app.world)
+            .rally_hits()
+    };
+    while served_hits(&mut app) < 4 {
+        app.update();
+        assert_eq!(state(&app), AppState::Game);
+    }
+    assert_eq!(ball_count(&mut app), 2);
+}
+
+#[test]
+fn every_ball_scores_on_its_own() {
+    let mut app = app_with_multiball(
+        true,
+        Multiball {
+            interval: 1.0,
+            rally_hits: 5,
+            max_balls: 4,
+        },
+    );
+    app.init_resource::<Points>()
+        .add_systems(FixedUpdate, record_points.in_set(PhysicsSet::Victory));
+    start_match(&mut app);
+
+    let final_score = play_match(&mut app);
+    app.update();
+    assert_eq!(
+        app.world.resource::<Points>().0.len() as u32,
+        final_score.left_score + final_score.right_score
+    );
+    // Every ball leaves along with the match, extra balls included
+    assert_eq!(ball_count(&mut app), 0);
+}
+
+#[test]
+fn the_ball_arriving_first_is_the_most_threatening() {
+    let ball = |x: f32, velocity_x: f32| BallState {
+        position: Vec2::new(x, 0.0),
+        velocity: Vec2::new(velocity_x, 0.0),
+    };
+    let paddle_x = -600.0;
+
+    // Closer isn't always sooner
+    let slow = ball(-300.0, -100.0);
+    let fast = ball(200.0, -1000.0);
+    let leaving = ball(-500.0, 500.0);
+    let threat = most_threatening([slow, fast, leaving], paddle_x).unwrap();
+    assert_eq!(threat.position, fast.position);
+
+    // With nothing on its way, the closest ball is watched
+    let far = ball(400.0, 500.0);
+    let threat = most_threatening([far, leaving], paddle_x).unwrap();
+    assert_eq!(threat.position, leaving.position);
+    assert!(most_threatening([], paddle_x).is_none());
+}
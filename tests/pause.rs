@@ -0,0 +1,155 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy_pong::ball::Ball;
+use bevy_pong::controller::{GameSnapshot, PaddleController, PaddleControllers};
+use bevy_pong::game::{GameData, MatchClock};
+use bevy_pong::paddle::Side;
+use bevy_pong::pause::{PauseState, RestartMatch};
+use bevy_pong::physics::{Position, PHYSICS_TIMESTEP};
+use bevy_pong::replay::LastReplay;
+use bevy_pong::AppState;
+use common::*;
+
+fn pause_state(app: &App) -> PauseState {
+    *app.world.resource::<State<PauseState>>().get()
+}
+
+fn ball_position(app: &mut App) -> Vec2 {
+    app.world
+        .query_filtered::<&Position, With<Ball>>()
+        .single(&app.world)
+        .current
+}
+
+fn set_pause_state(app: &mut App, pause_state: PauseState) {
+    app.world
+        .resource_mut::<NextState<PauseState>>()
+        .set(pause_state);
+}
+
+#[test]
+fn pausing_freezes_the_match() {
+    let mut app = headless_app(false);
+    start_match(&mut app);
+    wait_for_serve(&mut app);
+    press(&mut app, KeyCode::Up);
+    step(&mut app, 30);
+
+    tap(&mut app, KeyCode::Escape);
+    app.update();
+    assert_eq!(pause_state(&app), PauseState::Paused);
+
+    let ball = ball_position(&mut app);
+    let paddle = paddle_position(&mut app, Side::Right);
+    let elapsed = app.world.resource::<MatchClock>().elapsed;
+    step(&mut app, 120);
+    assert_eq!(ball_position(&mut app), ball);
+    assert_eq!(paddle_position(&mut app, Side::Right), paddle);
+    assert_eq!(app.world.resource::<MatchClock>().elapsed, elapsed);
+
+    tap(&mut app, KeyCode::P);
+    app.update();
+    assert_eq!(pause_state(&app), PauseState::Running);
+    step(&mut app, 30);
+    assert_ne!(ball_position(&mut app), ball);
+    assert!(app.world.resource::<MatchClock>().elapsed > elapsed);
+}
+
+// Counts how many times it has been asked to move
+struct Counter(Arc<AtomicUsize>);
+
+impl PaddleController for Counter {
+    fn movement(&mut self, _snapshot: &GameSnapshot) -> f32 {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        0.0
+    }
+}
+
+#[test]
+fn controllers_are_not_asked_to_move_while_paused() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut app = headless_app(false);
+    let counter = calls.clone();
+    app.world
+        .resource_mut::<PaddleControllers>()
+        .set(Side::Right, move || Counter(counter.clone()));
+    start_match(&mut app);
+    step(&mut app, 10);
+    assert!(calls.load(Ordering::SeqCst) > 0);
+
+    tap(&mut app, KeyCode::Escape);
+    app.update();
+    assert_eq!(pause_state(&app), PauseState::Paused);
+    let paused_calls = calls.load(Ordering::SeqCst);
+    step(&mut app, 120);
+    assert_eq!(calls.load(Ordering::SeqCst), paused_calls);
+
+    tap(&mut app, KeyCode::Escape);
+    step(&mut app, 10);
+    assert!(calls.load(Ordering::SeqCst) > paused_calls);
+}
+
+#[test]
+fn restarting_starts_a_fresh_match() {
+    let mut app = headless_app(false);
+    start_match(&mut app);
+    while score(&mut app).is_some_and(|score| score.left_score + score.right_score == 0) {
+        app.update();
+    }
+
+    tap(&mut app, KeyCode::Escape);
+    app.update();
+    app.world.send_event(RestartMatch);
+    set_pause_state(&mut app, PauseState::Running);
+    app.update();
+
+    assert_eq!(state(&app), AppState::Game);
+    assert_eq!(pause_state(&app), PauseState::Running);
+    let score = score(&mut app).expect("Only the new match's score should exist");
+    assert_eq!((score.left_score, score.right_score), (0, 0));
+    assert_eq!(app.world.query::<&Ball>().iter(&app.world).count(), 1);
+    // Only matches played to the end replace the last replay
+    assert!(!app.world.contains_resource::<LastReplay>());
+}
+
+#[test]
+fn quitting_to_the_menu_cleans_up_the_match() {
+    let mut app = headless_app(true);
+    start_match(&mut app);
+    step(&mut app, 30);
+
+    tap(&mut app, KeyCode::P);
+    app.update();
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Menu);
+    set_pause_state(&mut app, PauseState::Running);
+    app.update();
+
+    assert_eq!(state(&app), AppState::Menu);
+    assert_eq!(pause_state(&app), PauseState::Running);
+    assert!(score(&mut app).is_none());
+    assert_eq!(app.world.query::<&Ball>().iter(&app.world).count(), 0);
+    assert!(!app.world.contains_resource::<GameData>());
+    assert!(!app.world.contains_resource::<LastReplay>());
+}
+
+#[test]
+fn time_spent_paused_is_not_recorded() {
+    let mut app = headless_app(false);
+    start_match(&mut app);
+    step(&mut app, 30);
+    tap(&mut app, KeyCode::Escape);
+    step(&mut app, 120);
+    tap(&mut app, KeyCode::Escape);
+
+    play_match(&mut app);
+    app.update();
+    let recorded_steps = app.world.resource::<LastReplay>().0.step_count();
+    let elapsed = app.world.resource::<MatchClock>().elapsed.as_secs_f32();
+    assert_eq!(recorded_steps, (elapsed / PHYSICS_TIMESTEP).round() as u32);
+}
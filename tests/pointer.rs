@@ -0,0 +1,67 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_pong::game::MatchSetup;
+use bevy_pong::paddle::Side;
+use bevy_pong::pointer::{Pointer, Pointers};
+use common::*;
+
+fn pointer_app(left_pointer: Option<Pointer>, right_pointer: Option<Pointer>) -> App {
+    let mut app = headless_app_with_setup(MatchSetup {
+        seed: Some(TEST_SEED),
+        left_pointer,
+        right_pointer,
+        ..default()
+    });
+    start_match(&mut app);
+    app
+}
+
+#[test]
+fn paddles_follow_the_mouse_no_faster_than_the_keys() {
+    let mut app = pointer_app(Some(Pointer::Mouse), None);
+    app.world.resource_mut::<Pointers>().mouse = Some(Vec2::new(0.0, 100.0));
+
+    // The left paddle chases the mouse while the right one is held up
+    press(&mut app, KeyCode::Up);
+    let start = paddle_position(&mut app, Side::Left);
+    step(&mut app, 5);
+    let chased = paddle_position(&mut app, Side::Left).y - start.y;
+    let held = paddle_position(&mut app, Side::Right).y - start.y;
+    assert!(chased > 0.0);
+    assert_eq!(chased, held);
+
+    // And settles once it gets there
+    step(&mut app, 60);
+    assert_eq!(paddle_position(&mut app, Side::Left).y, 100.0);
+    step(&mut app, 10);
+    assert_eq!(paddle_position(&mut app, Side::Left).y, 100.0);
+}
+
+#[test]
+fn touches_steer_the_paddle_on_their_half() {
+    let mut app = pointer_app(Some(Pointer::Touch), Some(Pointer::Touch));
+    app.world.resource_mut::<Pointers>().touches =
+        vec![Vec2::new(-200.0, 80.0), Vec2::new(250.0, -60.0)];
+    step(&mut app, 60);
+    assert_eq!(paddle_position(&mut app, Side::Left).y, 80.0);
+    assert_eq!(paddle_position(&mut app, Side::Right).y, -60.0);
+
+    // Lifting the finger leaves the paddle where it is
+    app.world.resource_mut::<Pointers>().touches.clear();
+    step(&mut app, 30);
+    assert_eq!(paddle_position(&mut app, Side::Left).y, 80.0);
+    assert_eq!(paddle_position(&mut app, Side::Right).y, -60.0);
+}
+
+#[test]
+fn paddles_without_a_pointer_ignore_it() {
+    let mut app = pointer_app(None, Some(Pointer::Touch));
+    app.world.insert_resource(Pointers {
+        mouse: Some(Vec2::new(-200.0, 100.0)),
+        touches: vec![Vec2::new(-200.0, 100.0)],
+    });
+    step(&mut app, 60);
+    assert_eq!(paddle_position(&mut app, Side::Left).y, 0.0);
+    assert_eq!(paddle_position(&mut app, Side::Right).y, 0.0);
+}
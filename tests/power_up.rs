@@ -0,0 +1,287 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_pong::arena::{Arena, Obstacle, ObstacleMotion, Segment, WALL_THICKNESS};
+use bevy_pong::ball::{Ball, ExtraBall};
+use bevy_pong::controller::{PaddleController, PaddleControllers};
+use bevy_pong::game::MatchSetup;
+use bevy_pong::paddle::{PaddleHeight, Side};
+use bevy_pong::physics::{closest_point_on_segment, PhysicsSet, Position};
+use bevy_pong::power_up::{ActivePowerUps, PowerUp, PowerUpKind, POWER_UP_SIZE};
+use bevy_pong::rules::{MatchRules, Multiball, PowerUps};
+use bevy_pong::WINDOW_WIDTH;
+use common::*;
+
+fn app_with_controller<C: PaddleController>(
+    rules: MatchRules,
+    factory: impl Fn() -> C + Send + Sync + 'static,
+) -> App {
+    let mut app = headless_app(false);
+    app.insert_resource(rules);
+    app.world
+        .resource_mut::<PaddleControllers>()
+        .set(Side::Right, factory);
+    start_match(&mut app);
+    app
+}
+
+fn served_ball(app: &mut App) -> (Vec2, Vec2, Option<Side>) {
+    let (position, ball) = app
+        .world
+        .query_filtered::<(&Position, &Ball), Without<ExtraBall>>()
+        .single(&app.world);
+    (position.current, ball.velocity(), ball.last_hitter())
+}
+
+fn wait_for_hit(app: &mut App, side: Side) {
+    for _ in 0..MAX_MATCH_FRAMES {
+        if served_ball(app).2 == Some(side) {
+            return;
+        }
+        app.update();
+    }
+    panic!("The ball was never hit by {side:?}");
+}
+
+// Puts a power-up right where the ball is about to be
+fn place_on_ball(app: &mut App, kind: PowerUpKind) {
+    let (position, velocity, _) = served_ball(app);
+    place(app, kind, position + velocity * FRAME_TIME.as_secs_f32());
+}
+
+fn place(app: &mut App, kind: PowerUpKind, position: Vec2) {
+    app.world.spawn((
+        Position::new(position),
+        PowerUp {
+            kind,
+            remaining: 60.0,
+        },
+    ));
+}
+
+fn power_ups(app: &mut App) -> Vec<(Entity, Vec2)> {
+    app.world
+        .query_filtered::<(Entity, &Position), With<PowerUp>>()
+        .iter(&app.world)
+        .map(|(entity, position)| (entity, position.current))
+        .collect()
+}
+
+fn height(app: &mut App, side: Side) -> f32 {
+    app.world
+        .query::<(&PaddleHeight, &Side)>()
+        .iter(&app.world)
+        .find(|(_, paddle_side)| **paddle_side == side)
+        .map(|(height, _)| height.0)
+        .unwrap()
+}
+
+fn activate(app: &mut App, kind: PowerUpKind, side: Side) {
+    app.world
+        .resource_mut::<ActivePowerUps>()
+        .activate(kind, side);
+}
+
+#[test]
+fn there_are_no_power_ups_unless_the_rules_ask_for_them() {
+    let mut app = headless_app(false);
+    start_match(&mut app);
+    for _ in 0..60 * 30 {
+        app.update();
+        assert!(power_ups(&mut app).is_empty());
+    }
+}
+
+#[test]
+fn power_ups_turn_up_in_the_middle_and_go_away_again() {
+    let mut app = headless_app(false);
+    app.insert_resource(MatchRules {
+        // Nobody gets to pick anything up while the ball is held
+        serve_countdown: 10.0,
+        power_ups: Some(PowerUps {
+            interval: 1.0,
+            max_on_board: 2,
+            lifetime: 3.0,
+        }),
+        ..default()
+    });
+    start_match(&mut app);
+
+    step(&mut app, 65);
+    let first = power_ups(&mut app);
+    assert_eq!(first.len(), 1);
+    for _ in 0..60 * 3 {
+        app.update();
+        let waiting = power_ups(&mut app);
+        assert!(waiting.len() <= 2);
+        for (_, position) in waiting {
+            assert!(position.x.abs() <= WINDOW_WIDTH / 4.0);
+        }
+    }
+    assert!(power_ups(&mut app)
+        .iter()
+        .all(|(entity, _)| *entity != first[0].0));
+}
+
+#[test]
+fn power_ups_go_to_the_side_that_hit_the_ball_last() {
+    let mut app = app_with_controller(MatchRules::default(), || Follower);
+    wait_for_hit(&mut app, Side::Right);
+    place_on_ball(&mut app, PowerUpKind::Grow);
+    step(&mut app, 2);
+    assert!(power_ups(&mut app).is_empty());
+    assert_eq!(height(&mut app, Side::Right), 180.0);
+    assert_eq!(height(&mut app, Side::Left), 120.0);
+
+    // Shrinking goes the other way, onto the opponent
+    place_on_ball(&mut app, PowerUpKind::Shrink);
+    step(&mut app, 2);
+    assert_eq!(height(&mut app, Side::Left), 72.0);
+
+    // Both wear off after ten seconds
+    step(&mut app, 60 * 10);
+    assert_eq!(height(&mut app, Side::Right), 120.0);
+    assert_eq!(height(&mut app, Side::Left), 120.0);
+    assert_eq!(app.world.resource::<ActivePowerUps>().iter().count(), 0);
+}
+
+#[test]
+fn balls_nobody_has_hit_pass_straight_through() {
+    let mut app = headless_app(false);
+    start_match(&mut app);
+    place(&mut app, PowerUpKind::Grow, Vec2::ZERO);
+    wait_for_serve(&mut app);
+    step(&mut app, 30);
+    assert_eq!(power_ups(&mut app).len(), 1);
+    assert_eq!(app.world.resource::<ActivePowerUps>().iter().count(), 0);
+}
+
+#[test]
+fn multiball_power_ups_send_off_another_ball() {
+    let mut app = app_with_controller(MatchRules::default(), || Follower);
+    wait_for_hit(&mut app, Side::Right);
+    place_on_ball(&mut app, PowerUpKind::Multiball);
+    step(&mut app, 2);
+    assert_eq!(app.world.query::<&Ball>().iter(&app.world).count(), 2);
+}
+
+#[test]
+fn multiball_power_ups_keep_to_the_most_balls_allowed() {
+    let rules = MatchRules {
+        multiball: Some(Multiball {
+            interval: 0.0,
+            rally_hits: 0,
+            max_balls: 1,
+        }),
+        ..default()
+    };
+    let mut app = app_with_controller(rules, || Follower);
+    wait_for_hit(&mut app, Side::Right);
+    place_on_ball(&mut app, PowerUpKind::Multiball);
+    step(&mut app, 2);
+    assert!(power_ups(&mut app).is_empty());
+    assert_eq!(app.world.query::<&Ball>().iter(&app.world).count(), 1);
+}
+
+#[test]
+fn power_ups_keep_clear_of_the_arena() {
+    // Most of the middle of the board is taken up by the arena
+    let wall = Segment {
+        start: Vec2::new(60.0, -200.0),
+        end: Vec2::new(60.0, 200.0),
+    };
+    let obstacle = Obstacle {
+        center: Vec2::new(-100.0, 0.0),
+        half_size: Vec2::new(40.0, 100.0),
+        motion: Some(ObstacleMotion {
+            offset: Vec2::new(20.0, 40.0),
+            period: 3.0,
+        }),
+    };
+    let mut app = headless_app_with_setup(MatchSetup {
+        seed: Some(TEST_SEED),
+        arena: Arena {
+            walls: vec![wall],
+            obstacles: vec![obstacle],
+            ..default()
+        },
+        ..default()
+    });
+    app.insert_resource(MatchRules {
+        serve_countdown: 60.0,
+        power_ups: Some(PowerUps {
+            interval: 0.1,
+            max_on_board: 100,
+            lifetime: 60.0,
+        }),
+        ..default()
+    });
+    start_match(&mut app);
+
+    step(&mut app, 60 * 10);
+    let waiting = power_ups(&mut app);
+    assert!(waiting.len() > 50, "{}", waiting.len());
+    let half_size = POWER_UP_SIZE / 2.0;
+    let reach = obstacle.reach() + half_size;
+    for (_, position) in waiting {
+        let offset = (position - obstacle.center).abs();
+        assert!(offset.x >= reach.x || offset.y >= reach.y, "{position}");
+        let closest = closest_point_on_segment(position, wall.start, wall.end);
+        assert!(
+            (position - closest).abs().max_element() >= half_size + WALL_THICKNESS / 2.0,
+            "{position}"
+        );
+    }
+}
+
+#[test]
+fn shields_turn_back_a_ball_that_would_have_scored() {
+    let mut app = app_with_controller(MatchRules::default(), || Dodger);
+    app.init_resource::<Points>()
+        .add_systems(FixedUpdate, record_points.in_set(PhysicsSet::Victory));
+    activate(&mut app, PowerUpKind::Shield, Side::Right);
+
+    // The first serve heads right, past the paddle getting out of its way
+    while app
+        .world
+        .resource::<ActivePowerUps>()
+        .is_active(PowerUpKind::Shield, Side::Right)
+    {
+        app.update();
+    }
+    assert!(app.world.resource::<Points>().0.is_empty());
+    let (position, velocity, _) = served_ball(&mut app);
+    assert!(
+        position.x > 0.0 && velocity.x < 0.0,
+        "{position} {velocity}"
+    );
+}
+
+#[test]
+fn slow_motion_slows_down_the_ball() {
+    let mut app = headless_app(false);
+    start_match(&mut app);
+    activate(&mut app, PowerUpKind::SlowMotion, Side::Left);
+    wait_for_serve(&mut app);
+
+    let (start, velocity, _) = served_ball(&mut app);
+    app.update();
+    let (end, _, _) = served_ball(&mut app);
+    let expected = velocity.length() * FRAME_TIME.as_secs_f32() * 0.6;
+    assert!(((end - start).length() - expected).abs() < 0.5);
+}
+
+#[test]
+fn curve_shots_bend_back_towards_the_middle() {
+    let mut app = app_with_controller(MatchRules::default(), || Follower);
+    activate(&mut app, PowerUpKind::Curve, Side::Right);
+    wait_for_hit(&mut app, Side::Right);
+
+    let (_, before, _) = served_ball(&mut app);
+    app.update();
+    let (_, after, _) = served_ball(&mut app);
+    assert!((after.length() - before.length()).abs() < 0.01);
+    // Two steps of turning at 1.2 radians a second
+    assert!((after.angle_between(before).abs() - 0.02).abs() < 0.001);
+    assert!(after.y.abs() < before.y.abs());
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_pong::ball::{Ball, PaddleHit};
+use bevy_pong::controller::PaddleControllers;
+use bevy_pong::paddle::Side;
+use bevy_pong::physics::PhysicsSet;
+use bevy_pong::rules::MatchRules;
+use common::*;
+
+// How many hits the rally was on and how fast the ball went after each hit
+#[derive(Resource, Default)]
+struct Rally(Vec<(u32, f32)>);
+
+fn record_rally(
+    mut rally: ResMut<Rally>,
+    mut paddle_hits: EventReader<PaddleHit>,
+    ball_query: Query<&Ball>,
+) {
+    for _ in paddle_hits.iter() {
+        let ball = ball_query.single();
+        rally.0.push((ball.rally_hits(), ball.velocity().length()));
+    }
+}
+
+fn ball(app: &mut App) -> (u32, Vec2) {
+    let ball = app.world.query::<&Ball>().single(&app.world);
+    (ball.rally_hits(), ball.velocity())
+}
+
+#[test]
+fn the_ball_speeds_up_with_every_hit_up_to_the_limit() {
+    let mut app = headless_app(false);
+    app.insert_resource(MatchRules {
+        ball_speed: 600.0,
+        ball_speed_increase: 100.0,
+        ball_max_speed: 900.0,
+        ..default()
+    })
+    .init_resource::<Rally>()
+    .add_systems(FixedUpdate, record_rally.in_set(PhysicsSet::Victory));
+    let mut controllers = app.world.resource_mut::<PaddleControllers>();
+    controllers.set(Side::Left, || Follower);
+    controllers.set(Side::Right, || Follower);
+    start_match(&mut app);
+    step(&mut app, 60 * 10);
+
+    let rally = &app.world.resource::<Rally>().0;
+    assert!(rally.len() >= 5, "{rally:?}");
+    for &(hits, speed) in rally.iter().take(5) {
+        let expected = (600.0 + 100.0 * (hits - 1) as f32).min(900.0);
+        assert!((speed - expected).abs() < 0.01, "{hits}: {speed}");
+    }
+}
+
+#[test]
+fn serving_starts_a_new_rally() {
+    let mut app = headless_app(false);
+    let serve_speed = {
+        let rules = app.world.resource::<MatchRules>();
+        rules.ball_speed * rules.ball_serve_multiplier
+    };
+    app.world
+        .resource_mut::<PaddleControllers>()
+        .set(Side::Right, || Follower);
+    start_match(&mut app);
+
+    // Wait for the right paddle to return the ball, which then gets past the
+    // left paddle standing still
+    let mut longest_rally = 0;
+    for _ in 0..MAX_MATCH_FRAMES {
+        app.update();
+        let score = score(&mut app).unwrap();
+        if score.right_score > 0 {
+            break;
+        }
+        longest_rally = longest_rally.max(ball(&mut app).0);
+    }
+    assert!(longest_rally > 0);
+
+    wait_for_serve(&mut app);
+    let (hits, velocity) = ball(&mut app);
+    assert_eq!(hits, 0);
+    assert!((velocity.length() - serve_speed).abs() < 0.01);
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_pong::ball::Ball;
+use bevy_pong::physics::Position;
+use bevy_pong::replay::{LastReplay, Replay, ReplayError, ReplayPlayback};
+use bevy_pong::AppState;
+use common::*;
+
+// Plays a two player match with both players wiggling their paddles about
+fn record_match() -> Replay {
+    let mut app = headless_app(false);
+    start_match(&mut app);
+
+    let mut frame = 0;
+    while state(&app) == AppState::Game && frame < MAX_MATCH_FRAMES {
+        match frame % 90 {
+            0 => press(&mut app, KeyCode::W),
+            20 => release(&mut app, KeyCode::W),
+            30 => press(&mut app, KeyCode::Down),
+            70 => release(&mut app, KeyCode::Down),
+            _ => {}
+        }
+        app.update();
+        frame += 1;
+    }
+    assert_eq!(state(&app), AppState::End);
+
+    app.world.resource::<LastReplay>().0.clone()
+}
+
+fn start_replay(replay: Replay) -> App {
+    let mut app = headless_app(false);
+    app.insert_resource(ReplayPlayback::new(replay));
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Replay);
+    app.update();
+    app
+}
+
+fn ball_position(app: &mut App) -> Vec2 {
+    app.world
+        .query_filtered::<&Position, With<Ball>>()
+        .single(&app.world)
+        .current
+}
+
+fn seek(app: &mut App, step: u32) {
+    app.world.resource_mut::<ReplayPlayback>().seek(step);
+    app.update();
+}
+
+#[test]
+fn recording_covers_every_point() {
+    let replay = record_match();
+    let last = *replay.scores().last().unwrap();
+
+    assert_eq!(last.left_score.max(last.right_score), 10);
+    assert_eq!(
+        replay.scores().len() as u32,
+        last.left_score + last.right_score
+    );
+    assert!(last.step < replay.step_count());
+}
+
+#[test]
+fn playback_reproduces_the_match() {
+    let replay = record_match();
+    let last = *replay.scores().last().unwrap();
+
+    let mut app = start_replay(replay);
+    assert_eq!(state(&app), AppState::Replay);
+    while !app.world.resource::<ReplayPlayback>().is_finished() {
+        app.update();
+    }
+    app.update();
+
+    let score = score(&mut app).unwrap();
+    assert_eq!(
+        (score.left_score, score.right_score),
+        (last.left_score, last.right_score)
+    );
+    // Replays never end the match themselves
+    assert_eq!(state(&app), AppState::Replay);
+}
+
+#[test]
+fn seeking_matches_playing_through() {
+    let replay = record_match();
+    let target = replay.step_count() / 2;
+
+    let mut played = start_replay(replay.clone());
+    while played.world.resource::<ReplayPlayback>().step() < target {
+        played.update();
+    }
+    // Frames may overshoot the target by a step, so seek to where it landed
+    let landed = played.world.resource::<ReplayPlayback>().step();
+    let played_position = ball_position(&mut played);
+
+    let mut seeked = start_replay(replay);
+    seek(&mut seeked, landed);
+    assert_eq!(seeked.world.resource::<ReplayPlayback>().step(), landed);
+    assert_eq!(ball_position(&mut seeked), played_position);
+
+    // Going backwards simulates everything again from the start
+    seek(&mut seeked, 10);
+    seek(&mut seeked, landed);
+    assert_eq!(ball_position(&mut seeked), played_position);
+}
+
+#[test]
+fn replays_survive_a_round_trip_to_disk() {
+    let replay = record_match();
+    let path = std::env::temp_dir().join(format!("pong-replay-{}.ron", std::process::id()));
+
+    replay.save(&path).unwrap();
+    let loaded = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.step_count(), replay.step_count());
+    assert_eq!(loaded.scores(), replay.scores());
+    assert_eq!(loaded.setup().seed, replay.setup().seed);
+}
+
+#[test]
+fn replays_with_unplayable_rules_or_arenas_are_rejected() {
+    let replay = record_match();
+    let path = std::env::temp_dir().join(format!("pong-bad-replay-{}.ron", std::process::id()));
+    replay.save(&path).unwrap();
+    let saved = std::fs::read_to_string(&path).unwrap();
+
+    let load_edited = |from: &str, to: &str| {
+        assert!(saved.contains(from), "{from}");
+        std::fs::write(&path, saved.replace(from, to)).unwrap();
+        Replay::load(&path)
+    };
+    let rules = load_edited(
+        "power_ups:None",
+        "power_ups:Some((interval:0.0,max_on_board:2,lifetime:3.0))",
+    );
+    let arena = load_edited("goal_height:None", "goal_height:Some(1.0)");
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(matches!(rules, Err(ReplayError::InvalidRules(_))));
+    assert!(matches!(arena, Err(ReplayError::InvalidArena(_))));
+}
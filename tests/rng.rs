@@ -0,0 +1,69 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_pong::ball::Ball;
+use bevy_pong::game::MatchSetup;
+use bevy_pong::physics::Position;
+use bevy_pong::rng::GameRng;
+use bevy_pong::AppState;
+use common::*;
+
+fn seeded_app(seed: Option<u64>) -> App {
+    let mut app = headless_app_with_setup(MatchSetup {
+        is_single_player: true,
+        seed,
+        ..default()
+    });
+    start_match(&mut app);
+    app
+}
+
+// Records where the ball was on every frame for the first few seconds
+fn ball_path(app: &mut App) -> Vec<Vec2> {
+    (0..300)
+        .map(|_| {
+            app.update();
+            app.world
+                .query_filtered::<&Position, With<Ball>>()
+                .single(&app.world)
+                .current
+        })
+        .collect()
+}
+
+// Plays out a whole match, returning the final score and how long it took
+fn play_seeded_match(seed: u64) -> (u32, u32, usize) {
+    let mut app = seeded_app(Some(seed));
+    let mut last_score = score(&mut app).unwrap();
+    let mut frames = 0;
+    while state(&app) == AppState::Game {
+        app.update();
+        frames += 1;
+        if let Some(score) = score(&mut app) {
+            last_score = score;
+        }
+    }
+    (last_score.left_score, last_score.right_score, frames)
+}
+
+#[test]
+fn same_seed_plays_the_same_match() {
+    assert_eq!(play_seeded_match(42), play_seeded_match(42));
+}
+
+#[test]
+fn different_seeds_serve_differently() {
+    let first = ball_path(&mut seeded_app(Some(1)));
+    let second = ball_path(&mut seeded_app(Some(2)));
+    assert_ne!(first, second);
+}
+
+#[test]
+fn unseeded_match_can_be_reproduced_from_its_seed() {
+    let mut app = seeded_app(None);
+    let seed = app.world.resource::<GameRng>().seed();
+    let original = ball_path(&mut app);
+
+    let replayed = ball_path(&mut seeded_app(Some(seed)));
+    assert_eq!(original, replayed);
+}
@@ -0,0 +1,172 @@
+mod common;
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy_pong::replay::{LastReplay, ReplayPlayback};
+use bevy_pong::rules::{MatchRules, RulesError, VictoryCondition};
+use bevy_pong::AppState;
+use common::*;
+
+// Writes out a settings file unique to the calling test
+fn settings_file(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("pong-{}-{name}.ron", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn load(name: &str, contents: &str) -> Result<MatchRules, RulesError> {
+    let path = settings_file(name, contents);
+    let rules = MatchRules::load(&path);
+    std::fs::remove_file(path).unwrap();
+    rules
+}
+
+fn invalid_field(result: Result<MatchRules, RulesError>) -> &'static str {
+    match result {
+        Err(RulesError::Invalid { field, .. }) => field,
+        _ => panic!("The rules should have been rejected"),
+    }
+}
+
+#[test]
+fn shipped_settings_match_the_defaults() {
+    let rules = MatchRules::load(concat!(env!("CARGO_MANIFEST_DIR"), "/settings.ron")).unwrap();
+    assert_eq!(rules, MatchRules::default());
+}
+
+#[test]
+fn missing_rules_keep_their_defaults() {
+    let rules = load("partial", "(victory: FirstTo(3), paddle_speed: 250.0)").unwrap();
+    assert_eq!(rules.victory, VictoryCondition::FirstTo(3));
+    assert_eq!(rules.paddle_speed, 250.0);
+    assert_eq!(rules.ball_speed, MatchRules::default().ball_speed);
+}
+
+#[test]
+fn bad_values_are_rejected_by_name() {
+    assert_eq!(
+        invalid_field(load("victory", "(victory: FirstTo(0))")),
+        "victory"
+    );
+    assert_eq!(
+        invalid_field(load("height", "(paddle_height: 1000.0)")),
+        "paddle_height"
+    );
+    assert_eq!(
+        invalid_field(load("speed", "(ball_speed: -5.0)")),
+        "ball_speed"
+    );
+    assert_eq!(
+        invalid_field(load("serve", "(ball_serve_multiplier: 3.0)")),
+        "ball_serve_multiplier"
+    );
+    assert_eq!(
+        invalid_field(load(
+            "momentum",
+            "(paddle_momentum: Some((acceleration: 0.0)))"
+        )),
+        "paddle_momentum.acceleration"
+    );
+    assert_eq!(
+        invalid_field(load("spin", "(paddle_spin: 5.0)")),
+        "paddle_spin"
+    );
+    assert_eq!(
+        invalid_field(load("multiball", "(multiball: Some((max_balls: 0)))")),
+        "multiball.max_balls"
+    );
+    assert_eq!(
+        invalid_field(load("power_ups", "(power_ups: Some((lifetime: 0.0)))")),
+        "power_ups.lifetime"
+    );
+
+    let error = load("message", "(paddle_height: 1000.0)").unwrap_err();
+    assert!(error.to_string().contains("paddle_height"));
+}
+
+#[test]
+fn bad_values_in_a_section_are_reported_with_the_section() {
+    // Both sections have an `interval`, so the message has to say which
+    let error = load("multiball_interval", "(multiball: Some((interval: -1.0)))").unwrap_err();
+    assert!(
+        error.to_string().contains("`multiball.interval`"),
+        "{error}"
+    );
+    let error = load("power_ups_interval", "(power_ups: Some((interval: 0.0)))").unwrap_err();
+    assert!(
+        error.to_string().contains("`power_ups.interval`"),
+        "{error}"
+    );
+    let error = load(
+        "momentum_friction",
+        "(paddle_momentum: Some((friction: -1.0)))",
+    )
+    .unwrap_err();
+    assert!(
+        error.to_string().contains("`paddle_momentum.friction`"),
+        "{error}"
+    );
+}
+
+#[test]
+fn unknown_or_missing_files_are_reported() {
+    assert!(matches!(
+        load("typo", "(victory_point: 3)"),
+        Err(RulesError::Parse(_))
+    ));
+    assert!(MatchRules::load("does/not/exist.ron")
+        .unwrap_err()
+        .is_not_found());
+}
+
+#[test]
+fn match_ends_at_the_configured_points() {
+    let mut app = headless_app(false);
+    app.insert_resource(MatchRules {
+        victory: VictoryCondition::FirstTo(3),
+        ..default()
+    });
+    start_match(&mut app);
+
+    let final_score = play_match(&mut app);
+    assert_eq!(final_score.left_score.max(final_score.right_score), 3);
+}
+
+#[test]
+fn replays_use_the_rules_they_were_recorded_with() {
+    let recorded_rules = MatchRules {
+        victory: VictoryCondition::FirstTo(3),
+        ball_speed: 600.0,
+        ..default()
+    };
+    let mut app = headless_app(false);
+    app.insert_resource(recorded_rules.clone());
+    start_match(&mut app);
+    let final_score = play_match(&mut app);
+    app.update();
+    let replay = app.world.resource::<LastReplay>().0.clone();
+    assert_eq!(replay.rules(), &recorded_rules);
+
+    // Watch it back on a game configured with the default rules
+    let mut app = headless_app(false);
+    app.insert_resource(ReplayPlayback::new(replay));
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Replay);
+    app.update();
+    assert_eq!(app.world.resource::<MatchRules>(), &recorded_rules);
+    while !app.world.resource::<ReplayPlayback>().is_finished() {
+        app.update();
+    }
+    app.update();
+    let replayed_score = score(&mut app).unwrap();
+    assert_eq!(replayed_score.left_score, final_score.left_score);
+    assert_eq!(replayed_score.right_score, final_score.right_score);
+
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Menu);
+    app.update();
+    assert_eq!(app.world.resource::<MatchRules>(), &MatchRules::default());
+}
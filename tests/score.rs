@@ -0,0 +1,84 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_pong::ball::PaddleHit;
+use bevy_pong::paddle::Side;
+use bevy_pong::physics::PhysicsSet;
+use bevy_pong::score::{PointScored, ScoreChanged};
+use common::*;
+
+// Every score change, along with how many paddle hits each rally took
+#[derive(Resource, Default)]
+struct Tally {
+    changes: Vec<(u32, u32)>,
+    hits: u32,
+    rallies: Vec<u32>,
+}
+
+fn record_tally(
+    mut tally: ResMut<Tally>,
+    mut paddle_hits: EventReader<PaddleHit>,
+    mut point_events: EventReader<PointScored>,
+    mut change_events: EventReader<ScoreChanged>,
+) {
+    tally.hits += paddle_hits.iter().count() as u32;
+    for _ in point_events.iter() {
+        let hits = std::mem::take(&mut tally.hits);
+        tally.rallies.push(hits);
+    }
+    for change in change_events.iter() {
+        tally
+            .changes
+            .push((change.0.left_score, change.0.right_score));
+    }
+}
+
+fn app_recording_points() -> App {
+    let mut app = headless_app(true);
+    app.init_resource::<Points>()
+        .init_resource::<Tally>()
+        .add_systems(
+            FixedUpdate,
+            (record_points, record_tally).in_set(PhysicsSet::Victory),
+        );
+    start_match(&mut app);
+    app
+}
+
+#[test]
+fn every_point_is_announced_exactly_once() {
+    let mut app = app_recording_points();
+    let final_score = play_match(&mut app);
+
+    let points = &app.world.resource::<Points>().0;
+    let tally = app.world.resource::<Tally>();
+    let total = final_score.left_score + final_score.right_score;
+    assert_eq!(points.len() as u32, total);
+    assert_eq!(tally.changes.len() as u32, total);
+
+    // Each change adds a single point, for the side that won it
+    let mut score = (0, 0);
+    for (point, &change) in points.iter().zip(&tally.changes) {
+        match point.side {
+            Side::Left => score.0 += 1,
+            Side::Right => score.1 += 1,
+        }
+        assert_eq!(change, score);
+    }
+    assert_eq!(score, (final_score.left_score, final_score.right_score));
+}
+
+#[test]
+fn points_tell_how_the_rally_went() {
+    let mut app = app_recording_points();
+    play_match(&mut app);
+
+    let points = &app.world.resource::<Points>().0;
+    let tally = app.world.resource::<Tally>();
+    assert!(tally.rallies.iter().any(|&hits| hits > 0));
+    for (point, &hits) in points.iter().zip(&tally.rallies) {
+        assert_eq!(point.rally_hits, hits);
+        // The ball has to cross at least half of the board after the serve
+        assert!(point.duration.as_secs_f32() > 0.5, "{point:?}");
+    }
+}
@@ -0,0 +1,195 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_pong::ball::Ball;
+use bevy_pong::controller::PaddleControllers;
+use bevy_pong::paddle::Side;
+use bevy_pong::physics::Position;
+use bevy_pong::replay::{LastReplay, ReplayPlayback};
+use bevy_pong::rules::{MatchRules, ServeRule};
+use bevy_pong::AppState;
+use common::*;
+
+fn manual_serve() -> MatchRules {
+    MatchRules {
+        manual_serve: true,
+        serve_countdown: 0.5,
+        ..default()
+    }
+}
+
+// The ball's velocity right as it gets served, if it was served this frame
+fn serve_after_update(app: &mut App) -> Option<Vec2> {
+    let mut balls = app.world.query::<&Ball>();
+    let was_held = balls.get_single(&app.world).is_ok_and(Ball::is_held);
+    app.update();
+    let ball = balls.get_single(&app.world).ok()?;
+    (was_held && !ball.is_held()).then(|| ball.velocity())
+}
+
+fn ball(app: &mut App) -> (Vec2, Vec2, bool) {
+    let (position, ball) = app.world.query::<(&Position, &Ball)>().single(&app.world);
+    (position.current, ball.velocity(), ball.is_held())
+}
+
+#[test]
+fn the_ball_is_held_until_the_countdown_is_over() {
+    let mut app = headless_app(false);
+    start_match(&mut app);
+
+    // The default countdown is three seconds
+    step(&mut app, 60 * 2);
+    assert_eq!(ball(&mut app), (Vec2::ZERO, Vec2::ZERO, true));
+    step(&mut app, 62);
+    let (position, velocity, is_held) = ball(&mut app);
+    assert!(!is_held);
+    // The left side serves first, towards the right
+    assert!(
+        velocity.x > 0.0 && position.x > 0.0,
+        "{velocity} {position}"
+    );
+}
+
+#[test]
+fn serve_rules_pick_who_serves_next() {
+    use Side::*;
+    assert_eq!(ServeRule::Alternate.next_server(Left, Left), Right);
+    assert_eq!(ServeRule::Alternate.next_server(Right, Left), Left);
+    assert_eq!(ServeRule::Loser.next_server(Left, Left), Right);
+    assert_eq!(ServeRule::Loser.next_server(Left, Right), Left);
+    assert_eq!(ServeRule::Winner.next_server(Left, Right), Right);
+    assert_eq!(ServeRule::Winner.next_server(Right, Left), Left);
+}
+
+#[test]
+fn points_are_followed_by_a_serve_from_the_winner() {
+    let mut app = headless_app(false);
+    app.insert_resource(MatchRules {
+        serve_rule: ServeRule::Winner,
+        ..default()
+    });
+    start_match(&mut app);
+    while score(&mut app).is_some_and(|score| score.left_score + score.right_score == 0) {
+        app.update();
+    }
+
+    let score = score(&mut app).unwrap();
+    let winner = if score.left_score > 0 {
+        Side::Left
+    } else {
+        Side::Right
+    };
+    let server = app.world.query::<&Ball>().single(&app.world).server();
+    assert_eq!(server, winner);
+    assert_eq!(ball(&mut app), (Vec2::ZERO, Vec2::ZERO, true));
+}
+
+#[test]
+fn players_serving_by_hand_carry_the_ball_until_they_serve() {
+    let mut app = headless_app(false);
+    app.insert_resource(manual_serve());
+    start_match(&mut app);
+
+    // Long after the countdown, the ball still sits in front of the left
+    // paddle and goes wherever it goes
+    step(&mut app, 60);
+    let (position, _, is_held) = ball(&mut app);
+    assert!(is_held);
+    assert!(position.x < paddle_position(&mut app, Side::Right).x / 2.0);
+    press(&mut app, KeyCode::W);
+    step(&mut app, 20);
+    release(&mut app, KeyCode::W);
+    let (carried, _, _) = ball(&mut app);
+    assert!(carried.y > position.y);
+    assert_eq!(carried.y, paddle_position(&mut app, Side::Left).y);
+
+    // Only the serving player's key sends it off
+    tap(&mut app, KeyCode::Left);
+    assert!(ball(&mut app).2);
+    tap(&mut app, KeyCode::D);
+    let (_, velocity, is_held) = ball(&mut app);
+    assert!(!is_held);
+    assert!(velocity.x > 0.0);
+}
+
+#[test]
+fn replays_serve_when_the_players_did() {
+    let mut app = headless_app(false);
+    app.insert_resource(manual_serve());
+    start_match(&mut app);
+
+    // Both players take their time serving, at an odd moment each point
+    let mut frame = 0;
+    let mut serves = Vec::new();
+    while state(&app) == AppState::Game && frame < MAX_MATCH_FRAMES {
+        match frame % 157 {
+            100 => {
+                press(&mut app, KeyCode::D);
+                press(&mut app, KeyCode::Left);
+            }
+            101 => {
+                release(&mut app, KeyCode::D);
+                release(&mut app, KeyCode::Left);
+            }
+            _ => {}
+        }
+        serves.extend(serve_after_update(&mut app));
+        frame += 1;
+    }
+    assert_eq!(state(&app), AppState::End);
+    assert!(serves.len() >= 10);
+    assert_replay_serves_the_same(&app, &serves);
+}
+
+#[test]
+fn replays_serve_for_sides_that_were_controlled() {
+    let mut app = headless_app(false);
+    app.insert_resource(manual_serve());
+    app.world
+        .resource_mut::<PaddleControllers>()
+        .set(Side::Left, || Follower);
+    start_match(&mut app);
+
+    // The controller serves on its own, the player on the right by hand
+    let mut frame = 0;
+    let mut serves = Vec::new();
+    while state(&app) == AppState::Game && frame < MAX_MATCH_FRAMES {
+        if frame % 157 == 100 {
+            press(&mut app, KeyCode::Left);
+        } else if frame % 157 == 101 {
+            release(&mut app, KeyCode::Left);
+        }
+        serves.extend(serve_after_update(&mut app));
+        frame += 1;
+    }
+    assert_eq!(state(&app), AppState::End);
+    assert!(serves.len() >= 10);
+
+    assert_replay_serves_the_same(&app, &serves);
+}
+
+// Plays back the replay of the match that `app` just finished, which has to
+// serve the ball exactly as the match did and end on the same score
+fn assert_replay_serves_the_same(app: &App, serves: &[Vec2]) {
+    let replay = app.world.resource::<LastReplay>().0.clone();
+    let last = *replay.scores().last().unwrap();
+
+    let mut app = headless_app(false);
+    app.insert_resource(ReplayPlayback::new(replay));
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Replay);
+    app.update();
+    let mut replayed_serves = Vec::new();
+    while !app.world.resource::<ReplayPlayback>().is_finished() {
+        replayed_serves.extend(serve_after_update(&mut app));
+    }
+    app.update();
+
+    assert_eq!(replayed_serves, serves);
+    let score = score(&mut app).unwrap();
+    assert_eq!(
+        (score.left_score, score.right_score),
+        (last.left_score, last.right_score)
+    );
+}
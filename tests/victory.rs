@@ -0,0 +1,115 @@
+mod common;
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_pong::game::{GameData, MatchClock};
+use bevy_pong::paddle::Side;
+use bevy_pong::rules::{MatchRules, VictoryCondition};
+use bevy_pong::score::Score;
+use bevy_pong::AppState;
+use common::*;
+
+fn points(left_score: u32, right_score: u32) -> Score {
+    Score {
+        left_score,
+        right_score,
+        ..default()
+    }
+}
+
+#[test]
+fn first_to_ends_on_the_target() {
+    let victory = VictoryCondition::FirstTo(5);
+    assert_eq!(victory.winner(&points(4, 4), Duration::ZERO), None);
+    assert_eq!(
+        victory.winner(&points(5, 4), Duration::ZERO),
+        Some(Side::Left)
+    );
+    assert_eq!(
+        victory.winner(&points(2, 5), Duration::ZERO),
+        Some(Side::Right)
+    );
+}
+
+#[test]
+fn win_by_two_plays_through_deuce() {
+    let victory = VictoryCondition::WinByTwo(5);
+    assert_eq!(victory.winner(&points(5, 4), Duration::ZERO), None);
+    assert_eq!(victory.winner(&points(6, 6), Duration::ZERO), None);
+    assert_eq!(
+        victory.winner(&points(8, 6), Duration::ZERO),
+        Some(Side::Left)
+    );
+    assert_eq!(
+        victory.winner(&points(3, 5), Duration::ZERO),
+        Some(Side::Right)
+    );
+}
+
+#[test]
+fn timed_matches_go_to_sudden_death_when_level() {
+    let victory = VictoryCondition::Timed(60);
+    let before = Duration::from_secs(59);
+    let after = Duration::from_secs(61);
+    assert_eq!(victory.winner(&points(9, 0), before), None);
+    assert_eq!(victory.winner(&points(3, 1), after), Some(Side::Left));
+    assert_eq!(victory.winner(&points(2, 2), after), None);
+    assert_eq!(victory.winner(&points(2, 3), after), Some(Side::Right));
+}
+
+#[test]
+fn sets_are_won_before_the_match() {
+    let victory = VictoryCondition::BestOfSets { sets: 3, points: 5 };
+    assert_eq!(victory.set_winner(&points(4, 4)), None);
+    assert_eq!(victory.set_winner(&points(5, 2)), Some(Side::Left));
+    assert_eq!(VictoryCondition::FirstTo(5).set_winner(&points(5, 2)), None);
+
+    let one_set_each = Score {
+        left_sets: 1,
+        right_sets: 1,
+        ..default()
+    };
+    assert_eq!(victory.winner(&one_set_each, Duration::ZERO), None);
+    let two_sets = Score {
+        right_sets: 2,
+        ..one_set_each
+    };
+    assert_eq!(victory.winner(&two_sets, Duration::ZERO), Some(Side::Right));
+}
+
+#[test]
+fn timed_match_ends_once_the_clock_runs_out() {
+    let mut app = headless_app(false);
+    app.insert_resource(MatchRules {
+        victory: VictoryCondition::Timed(5),
+        ..default()
+    });
+    start_match(&mut app);
+
+    let final_score = play_match(&mut app);
+    assert_eq!(state(&app), AppState::End);
+    assert!(app.world.resource::<MatchClock>().elapsed >= Duration::from_secs(5));
+    assert_ne!(final_score.left_score, final_score.right_score);
+    assert_eq!(
+        app.world.resource::<GameData>().is_left_win,
+        final_score.left_score > final_score.right_score
+    );
+}
+
+#[test]
+fn sets_match_ends_after_enough_sets() {
+    let mut app = headless_app(false);
+    app.insert_resource(MatchRules {
+        victory: VictoryCondition::BestOfSets { sets: 3, points: 2 },
+        ..default()
+    });
+    start_match(&mut app);
+
+    let final_score = play_match(&mut app);
+    assert_eq!(state(&app), AppState::End);
+    assert_eq!(final_score.left_sets.max(final_score.right_sets), 2);
+    assert!(final_score.left_sets.min(final_score.right_sets) < 2);
+    // Points start over with every set
+    assert!(final_score.left_score < 2 && final_score.right_score < 2);
+}